@@ -1,17 +1,19 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-pub fn advent() {
-    let data = read_data();
-    let base_fuel: u32 = data.iter().map(|&m| base_fuel_required(m)).sum();
-    println!("Base Fuel Required: {}", base_fuel);
-    let full_fuel: u32 = data.iter().map(|&m| full_fuel_required(m)).sum();
-    println!("Full Fuel Required: {}", full_fuel);
+use crate::puzzle::{Answer, Puzzle};
+
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(1, parse, vec!(base_fuel, full_fuel))
+}
+
+fn parse(input: &str) -> Vec<u32> {
+    input.lines().map(|l| l.parse::<u32>().unwrap()).collect()
+}
+
+fn base_fuel(data: &[u32]) -> Answer {
+    Answer::new("Base Fuel Required", data.iter().map(|&m| base_fuel_required(m)).sum::<u32>())
 }
 
-fn read_data() -> Vec<u32> {
-    let reader = BufReader::new(File::open("data/day1.txt").expect("Cannot open"));
-    reader.lines().map(|l| l.unwrap().parse::<u32>().unwrap()).collect()
+fn full_fuel(data: &[u32]) -> Answer {
+    Answer::new("Full Fuel Required", data.iter().map(|&m| full_fuel_required(m)).sum::<u32>())
 }
 
 fn base_fuel_required(mass: u32) -> u32 {
@@ -34,6 +36,7 @@ fn full_fuel_required(mass: u32) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
     parameterized_test::create!{ base_fuel, (mass, expected), {
             assert_eq!(base_fuel_required(mass), expected);
@@ -56,6 +59,6 @@ mod tests {
 
     #[test]
     fn read_file() {
-        assert!(read_data().len() > 0);
+        assert!(!parse(&input::read("day1.txt")).is_empty());
     }
 }
\ No newline at end of file
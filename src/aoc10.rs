@@ -1,20 +1,24 @@
-use std::fs;
 use std::collections::HashSet;
-use crate::euclid::{Point, point, vector, Vector};
-use num_integer::Integer;
-use std::cmp::Ordering;
+use crate::euclid::{Point, point, vector, visible};
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let coords = read_data("data/day10.txt");
-    let max = find_best_location(&coords);
-    println!("Best Station at {} can see: {} asteroids", max.1, max.0);
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(10, parse, vec!(best_station, two_hundredth_vaporized))
+}
+
+fn parse(input: &str) -> HashSet<Point> {
+    to_coords(input.trim())
+}
 
-    let swept = sweep_all(max.1, &coords);
-    println!("200th asteroid vaporized is: {} ({})", swept[199], swept[199].x * 100 + swept[199].y);
+fn best_station(coords: &HashSet<Point>) -> Answer {
+    let max = find_best_location(coords);
+    Answer::new("Asteroids Visible From Best Station", max.0).with_detail("Station", max.1)
 }
 
-fn read_data(file: &str) -> HashSet<Point> {
-    to_coords(fs::read_to_string(file).expect("Cannot open").trim())
+fn two_hundredth_vaporized(coords: &HashSet<Point>) -> Answer {
+    let max = find_best_location(coords);
+    let swept = sweep_all(max.1, coords);
+    Answer::new("200th Asteroid Vaporized", swept[199].x * 100 + swept[199].y).with_detail("Asteroid", swept[199])
 }
 
 fn to_coords(input: &str) -> HashSet<Point> {
@@ -32,31 +36,10 @@ fn to_coords(input: &str) -> HashSet<Point> {
     ret
 }
 
-fn vector_between(p1: Point, p2: Point) -> Vector {
-    let v = p2 - p1;
-    match v {
-        Vector { x: 0, .. } => vector(0, v.y.signum()),
-        Vector { y: 0, ..} => vector(v.x.signum(), 0),
-        _ => {
-            let gcd = v.x.gcd(&v.y);
-            vector(v.x / gcd, v.y / gcd)
-        },
-    }
-}
-
-fn vectors_for(asteroid: Point, asteroids: &HashSet<Point>) -> HashSet<Vector> {
-    let mut ret = HashSet::new();
-    for a in asteroids {
-        if &asteroid == a { continue; }
-        ret.insert(vector_between(asteroid, *a));
-    }
-    ret
-}
-
 fn find_best_location(asteroids: &HashSet<Point>) -> (usize, Point) {
     let mut max: Option<(usize, Point)> = None;
     for asteroid in asteroids {
-        let visible = vectors_for(*asteroid, asteroids).len();
+        let visible = visible(*asteroid, asteroids.iter().cloned()).len();
         if max.is_none() || max.unwrap().0 < visible {
             max = Some((visible, *asteroid));
         }
@@ -64,35 +47,15 @@ fn find_best_location(asteroids: &HashSet<Point>) -> (usize, Point) {
     max.expect("No asteroids found?")
 }
 
-// https://old.reddit.com/r/adventofcode/comments/e8r1jx
-fn compare_angle(a: Vector, b: Vector) -> Ordering {
-    let da = a.x < 0;
-    let db = b.x < 0;
-    if da != db { return da.cmp(&db); }
-    if a.x == 0 && b.x == 0 { return a.y.signum().cmp(&b.y.signum()); }
-    0.cmp(&(a.x * b.y - a.y * b.x).signum())
-}
-
-fn sweep_order(vectors: &HashSet<Vector>) -> Vec<Vector> {
-    let mut vec: Vec<_> = vectors.iter().cloned().collect();
-    //vec.sort_by_key(|v| OrderedFloat(v.y as f64 / v.x as f64));
-    vec.sort_by(|a, b| compare_angle(*a, *b));
-    vec
-}
-
 fn sweep_all(laser: Point, asteroids: &HashSet<Point>) -> Vec<Point> {
     let mut asteroids = asteroids.clone();
     assert!(asteroids.remove(&laser)); // laser must be on an asteroid
     let mut ret = Vec::new();
     while !asteroids.is_empty() {
-        for vector in sweep_order(&vectors_for(laser, &asteroids)) {
-            for i in 1.. {
-                let point = laser + (vector * i);
-                if asteroids.remove(&point) {
-                    ret.push(point);
-                    break;
-                }
-            }
+        // Each rotation vaporizes every asteroid currently visible, in clockwise order
+        for point in visible(laser, asteroids.iter().cloned()) {
+            asteroids.remove(&point);
+            ret.push(point);
         }
     }
     ret
@@ -101,20 +64,14 @@ fn sweep_all(laser: Point, asteroids: &HashSet<Point>) -> Vec<Point> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
-    parameterized_test::create!{ reduced_vector, (p1, p2, expected), {
-        assert_eq!(vector_between(p1, p2), expected);
-    }}
-    reduced_vector!{
-        a: (point(5, 5), point(5, 5), vector(0, 0)),
-        b: (point(10, 0), point(0, 0), vector(-1, 0)),
-        c: (point(-2, -2), point(8, 0), vector(5, 1)),
-        d: (point(0, 0), point(5, 4), vector(5, 4)),
-        e: (point(0, 0), point(-4936, 6170), vector(-4, 5)),
+    fn read_data(file: &str) -> HashSet<Point> {
+        parse(&input::read(file))
     }
 
     parameterized_test::create!{ best_location, (file, count, coord), {
-        let coords = read_data(&format!("data/day10-example{}.txt", file));
+        let coords = read_data(&format!("day10-example{}.txt", file));
         assert_eq!(find_best_location(&coords), (count, coord));
     }}
     best_location!{
@@ -126,7 +83,7 @@ mod tests {
     }
 
     parameterized_test::create!{ vaporize, (file, count, points) ,{
-        let coords = read_data(&format!("data/day10-example{}.txt", file));
+        let coords = read_data(&format!("day10-example{}.txt", file));
         let max = find_best_location(&coords);
         let swept = sweep_all(max.1, &coords);
         assert_eq!(swept.len(), count);
@@ -1,100 +1,64 @@
 use crate::intcode::{Machine, State};
-use std::collections::HashMap;
-use crate::euclid::{point, Point, vector, Vector};
+use crate::euclid::{Direction4, Heading, point, Point, SparseGrid, Turn};
+use crate::puzzle::{Answer, Puzzle};
+use crate::console::Renderer;
 
-pub fn advent() {
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(11, Machine::from_program, vec!(painted_tiles, registration))
+}
+
+fn painted_tiles(image: &Machine) -> Answer {
     // Don't provide a hint, thereby disabling the interactive display, since it's slow and typically
     // larger than the shell window, which messes up the rendering.
-    let painted_tiles = paint(false, None).len();
-    println!("(Mis)painted Tiles: {}\n", painted_tiles);
-
-    println!("Registration:");
-    let hull = paint(true, Some((point(0, 0), point(42, 5))));
-    println!("{}", render(&hull));
+    Answer::new("(Mis)painted Tiles", paint(image, false, None).len())
 }
 
-fn read_data() -> Machine {
-    Machine::from_file("data/day11.txt")
+fn registration(image: &Machine) -> Answer {
+    let hull = paint(image, true, Some((point(0, 0), point(42, 5))));
+    Answer::new("Registration", render(&hull))
 }
 
-fn paint(paint_origin: bool, bounds_hint: Option<(Point, Point)>) -> HashMap<Point, i64> {
-    let mut machine = read_data();
-    let mut hull = HashMap::new();
-    let mut dir = Dir::Up;
-    let mut pos = point(0, 0);
+fn paint(image: &Machine, paint_origin: bool, bounds_hint: Option<(Point, Point)>) -> SparseGrid<i64> {
+    let mut machine = image.clone();
+    let mut hull = SparseGrid::new();
+    let mut robot = Heading::new(point(0, 0), Direction4::North);
     if paint_origin {
-        hull.insert(pos, 1);
+        hull.insert(robot.pos, 1);
     }
+    let mut renderer = Renderer::new().frame_rate(40).record("day11-hull");
     loop {
-        if interactive!() && bounds_hint.is_some() {
-            let image = render_debug(&hull, bounds_hint);
-            println!("{}\u{001B}[{}A", image, image.chars().filter(|&c| c=='\n').count()+1);
-            std::thread::sleep(std::time::Duration::from_millis(25));
+        if let Some(bounds) = bounds_hint.filter(|_| renderer.enabled()) {
+            renderer.draw(hull.render_in(bounds, paint_char));
         }
-        machine.send_input(*hull.get(&pos).unwrap_or(&0));
+        // Unpainted panels are black
+        machine.send_input(*hull.get(robot.pos).unwrap_or(&0));
         let state = machine.run();
         let output = machine.read_output();
         assert!(output[0] == 0 || output[0] == 1);
-        hull.insert(pos, output[0]);
+        hull.insert(robot.pos, output[0]);
         assert!(output[1] == 0 || output[1] == 1);
-        dir = dir.rotate(output[1] == 1);
-        pos += dir.vec();
+        robot.turn(if output[1] == 1 { Turn::Right } else { Turn::Left });
+        robot.advance(1);
         match state {
             State::Input => {}
             State::Halt => { break; }
             _ => panic!(),
         }
-
     }
+    renderer.clear();
     hull
 }
 
-fn render(painted: &HashMap<Point, i64>) -> String {
-    render_debug(painted,Point::bounding_box(painted.keys().cloned()))
+fn render(painted: &SparseGrid<i64>) -> String {
+    painted.render(paint_char)
 }
 
-fn render_debug(painted: &HashMap<Point, i64>, bounds: Option<(Point, Point)>) -> String {
-    let mut out = String::new();
-    let bounds = bounds.or_else(|| Point::bounding_box(painted.keys().cloned())).unwrap_or((Point::ORIGIN, Point::ORIGIN));
-    for y in bounds.0.y..bounds.1.y+1 {
-        for x in bounds.0.x..bounds.1.x+1 {
-            let coord = point(x, y);
-            let paint = match painted.get(&coord) {
-                Some(0) => ' ',
-                Some(1) => '█',
-                None => '▒',
-                _ => panic!(),
-            };
-            out.push(paint);
-        }
-        out.push('\n');
-    }
-    out.pop();
-    out
-}
-
-#[derive(Copy, Clone)]
-enum Dir { Up, Down, Left, Right }
-
-impl Dir {
-    fn rotate(&self, rotate_right: bool) -> Dir {
-        use Dir::*;
-        match self {
-            Up => { if rotate_right { Right } else { Left } },
-            Down => { if rotate_right { Left } else { Right } },
-            Left => { if rotate_right { Up } else { Down } },
-            Right => { if rotate_right { Down } else { Up } },
-        }
-    }
-
-    fn vec(&self) -> Vector {
-        use Dir::*;
-        match self {
-            Up => vector(0, -1),
-            Down => vector(0, 1),
-            Left => vector(-1, 0),
-            Right => vector(1, 0),
-        }
+fn paint_char(_: Point, paint: Option<&i64>) -> char {
+    match paint {
+        Some(0) => ' ',
+        Some(1) => '█',
+        None => '▒',
+        _ => panic!(),
     }
 }
 
@@ -104,8 +68,7 @@ mod tests {
 
     #[test]
     fn execute() {
-        let hull = paint(true, None);
-        let bounding_box = Point::bounding_box(hull.keys().cloned()).expect("No points");
-        assert_eq!(bounding_box, (point(0, 0), point(42, 5)));
+        let hull = paint(&Machine::from_input("day11.txt"), true, None);
+        assert_eq!(hull.bounds(), Some((point(0, 0), point(42, 5))));
     }
 }
@@ -2,20 +2,39 @@ use crate::euclid3d::{point, Point, vector, Vector};
 use std::fmt;
 use std::collections::HashSet;
 use num_integer::Integer;
+use regex::Regex;
+use crate::error::ParseError;
+use crate::puzzle::{Answer, Puzzle};
 
-const INPUT: [Point; 4] = [
-    point(-10, -10,-13),
-    point(5, 5, -9),
-    point(3, 8, -16),
-    point(1, 3, -3),
-];
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(12, parse, vec!(energy_after_1k, steps_until_cycle))
+}
+
+fn parse(input: &str) -> Vec<Point> {
+    input.lines().map(|l| parse_moon(l).expect("Invalid")).collect()
+}
 
-pub fn advent() {
-    let (xs, ys, zs) = cycles(&INPUT);
+fn energy_after_1k(moons: &[Point]) -> Answer {
+    let (xs, ys, zs) = cycles(moons);
     let energy = state_at(&xs, &ys, &zs, 1000).iter().map(|m|m.energy()).sum::<u32>();
-    println!("After 1k steps, energy: {}", energy);
+    Answer::new("Energy After 1k Steps", energy)
+}
+
+fn steps_until_cycle(moons: &[Point]) -> Answer {
+    let (xs, ys, zs) = cycles(moons);
     // https://www.wolframalpha.com/input/?i=lcm+167624+231614+102356
-    println!("Cycles after: {} steps", cycle_len(xs, ys, zs));
+    Answer::new("Steps Until Cycle", cycle_len(xs, ys, zs))
+}
+
+fn parse_moon(s: &str) -> Result<Point, ParseError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^<x=(-?\d+), y=(-?\d+), z=(-?\d+)>$").unwrap();
+    }
+
+    let caps = regex_captures!(RE, s)?;
+    Ok(point(capture_group!(caps, 1).parse()?,
+             capture_group!(caps, 2).parse()?,
+             capture_group!(caps, 3).parse()?))
 }
 
 type Cycle = Vec<Vec<(i32, i32)>>;
@@ -110,11 +129,7 @@ impl Moon {
 
     #[cfg(test)]
     fn gravity(&mut self, g: Point) {
-        let p = self.pos;
-        let v = self.velocity;
-        self.velocity = vector(v.x + (g.x - p.x).signum(),
-                            v.y + (g.y - p.y).signum(),
-                            v.z + (g.z - p.z).signum());
+        self.velocity += (g - self.pos).signum();
     }
 
     #[cfg(test)]
@@ -123,9 +138,9 @@ impl Moon {
     }
 
     fn energy(&self) -> u32 {
-        let potential = (self.pos.x.abs() + self.pos.y.abs() + self.pos.z.abs()) as u32;
+        let potential = (self.pos - Point::ORIGIN).grid_len();
         let kinetic = self.velocity.grid_len();
-        potential * kinetic
+        (potential * kinetic) as u32
     }
 }
 
@@ -138,6 +153,7 @@ impl fmt::Display for Moon {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
     const EXAMPLE1: [Point; 4] = [
         point(-1, 0, 2),
@@ -153,6 +169,13 @@ mod tests {
         point(9, -8, -3),
     ];
 
+    #[test]
+    fn read_file() {
+        assert_eq!(parse(&input::read("day12.txt")), vec!(
+            point(-10, -10, -13), point(5, 5, -9), point(3, 8, -16), point(1, 3, -3)));
+        assert!(parse_moon("<x=1, y=2>").is_err());
+    }
+
     parameterized_test::create!{ energy, (points, steps, energy), {
         assert_eq!(simulate(&points, steps), energy);
         let (xs, ys, zs) = cycles(&points);
@@ -1,44 +1,51 @@
 use crate::intcode::Machine;
 use crate::intcode;
 use std::collections::HashMap;
-use crate::euclid::{Point, point};
+use crate::euclid::{Point, point, SparseGrid};
+use crate::puzzle::{Answer, Puzzle};
+use crate::console::{Color, Renderer};
 use std::fmt;
 
-pub fn advent() {
-    let mut machine = Machine::from_file("data/day13.txt");
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(13, Machine::from_program, vec!(initial_blocks, final_score))
+}
+
+fn initial_blocks(image: &Machine) -> Answer {
+    let mut machine = image.clone();
     let mut state = State::new();
     machine.run();
     state.update(&machine.read_output());
-    let blocks = state.find_tiles(Tile::Block).len();
+    Answer::new("Initial Blocks", state.find_tiles(Tile::Block).len())
+}
 
-    let score = play_game();
-    println!("Initial Blocks: {}\nFinal Score: {}", blocks, score);
+fn final_score(image: &Machine) -> Answer {
+    Answer::new("Final Score", play_game(image))
 }
 
-fn play_game() -> u32 {
-    let mut machine = Machine::from_file("data/day13.txt");
+fn play_game(image: &Machine) -> u32 {
+    let mut machine = image.clone();
     machine.set_state(0, 2);
     let mut state = State::new();
+    let mut renderer = Renderer::new().frame_rate(200).record("day13-breakout").palette(|c| match c {
+        '#' => Some(Color::Cyan),
+        '▔' => Some(Color::Green),
+        'O' => Some(Color::Red),
+        _ => None,
+    });
     loop {
         let machine_state = machine.run();
         let output = machine.read_output();
         assert!(!output.is_empty());
         state.update(&output);
         machine.send_input(state.find_move());
-        if interactive!() {
-            let display = state.to_string();
-            println!("{}\u{001B}[{}A", display, display.chars().filter(|&c| c == '\n').count() + 1);
-            std::thread::sleep(std::time::Duration::from_millis(5));
-        }
+        renderer.draw(&state);
         match machine_state {
             intcode::State::Input => {}
             intcode::State::Halt => { break; }
             _ => panic!(),
         }
     }
-    if interactive!() {
-        println!("{}", state);
-    }
+    renderer.finish(&state);
     state.score
 }
 
@@ -78,14 +85,14 @@ impl Tile {
 
 struct State {
     steps: u32,
-    tiles: HashMap<Point, Tile>,
+    tiles: SparseGrid<Tile>,
     score: u32,
 }
 
 impl State {
     const SCORE_COORD: Point = point(-1, 0);
 
-    fn new() -> State { State { steps: 0, tiles: HashMap::new(), score: 0 } }
+    fn new() -> State { State { steps: 0, tiles: SparseGrid::with_default(Tile::Empty), score: 0 } }
 
     fn update(&mut self, output: &[i64]) {
         assert_eq!(output.len() % 3, 0);
@@ -110,7 +117,7 @@ impl State {
     }
 
     fn find_tiles(&self, tile: Tile) -> Vec<Point> {
-        self.tiles.iter().filter(|(_, &v)| v == tile).map(|(&k, _)| k).collect()
+        self.tiles.iter().filter(|(_, &v)| v == tile).map(|(k, _)| k).collect()
     }
 
     fn find_move(&self) -> i64 {
@@ -126,17 +133,8 @@ impl State {
 
 impl fmt::Display for State {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut out = String::new();
-        let bounds = Point::bounding_box(self.tiles.keys().cloned()).expect("No points");
-        for y in bounds.0.y..bounds.1.y+1 {
-            for x in bounds.0.x..bounds.1.x + 1 {
-                let coord = point(x, y);
-                let tile = self.tiles.get(&coord).expect("Unknown tile");
-                out.push(tile.char());
-            }
-            out.push('\n');
-        }
-        write!(f, "{}Step: {:<4}  Blocks Left: {:<3} Score: {:>5}",
+        let out = self.tiles.render(|_, tile| tile.expect("Has default").char());
+        write!(f, "{}\nStep: {:<4}  Blocks Left: {:<3} Score: {:>5}",
                out, self.steps, self.find_tiles(Tile::Block).len(), self.score)
     }
 }
@@ -146,7 +144,7 @@ mod tests {
     use super::*;
 
     // Basic change-detector; problem statement doesn't offer any meaningful test cases
-    #[test] fn check_score() { assert_eq!(play_game(), 11140); }
+    #[test] fn check_score() { assert_eq!(play_game(&Machine::from_input("day13.txt")), 11140); }
 
     #[test]
     fn state_updates() {
@@ -154,8 +152,8 @@ mod tests {
         state.update(&[1,2,3,6,5,4]);
         state.update(&[-1,0,12345]);
         let tiles: HashMap<_, _> =
-            vec!((point(1,2), Tile::Paddle), (point(6, 5), Tile::Ball)).iter().cloned().collect();
-        assert_eq!(state.tiles, tiles);
+            [(point(1,2), Tile::Paddle), (point(6, 5), Tile::Ball)].iter().cloned().collect();
+        assert_eq!(state.tiles.iter().map(|(p, &t)| (p, t)).collect::<HashMap<_, _>>(), tiles);
         assert_eq!(state.score, 12345);
     }
 }
\ No newline at end of file
@@ -3,8 +3,8 @@ use std::ops::{AddAssign, Mul};
 use std::str::FromStr;
 use crate::error::ParseError;
 use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::pathfinding::{Edge, Graph};
+use crate::puzzle::{Answer, Puzzle};
 
 //
 // # Command to rename all the intermediate products to (maybe) make it easier to read
@@ -14,45 +14,67 @@ use std::io::{BufRead, BufReader};
 //   | sed --file=- data/day14.txt > /tmp/day14-simplified.txt
 //
 
-pub fn advent() {
-    let fuel = Ingredient { id: Ingredient::FUEL, amount: 1 };
-    let recipes = read_data("data/day14.txt");
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(14, parse, vec!(ore_for_one_fuel, fuel_for_ore))
+}
 
-    println!("1 FUEL: {} ORE", resolve(&recipes, &mut HashMap::new(), &fuel).amount);
+fn ore_for_one_fuel(recipes: &HashMap<u32, Recipe>) -> Answer {
+    let fuel = Ingredient { id: Ingredient::FUEL, amount: 1 };
+    Answer::new("ORE for 1 FUEL", resolve(recipes, &order(recipes, fuel.id), &mut HashMap::new(), &fuel).amount)
+}
 
-    let fuel_created = maximize(&recipes, &Ingredient { id: Ingredient::ORE, amount: 1000000000000u64 }).amount;
-    println!("10^12 ORE: {} FUEL", fuel_created);
+fn fuel_for_ore(recipes: &HashMap<u32, Recipe>) -> Answer {
+    let fuel_created = maximize(recipes, &Ingredient { id: Ingredient::ORE, amount: 1000000000000u64 }).amount;
+    Answer::new("FUEL from 10^12 ORE", fuel_created)
 }
 
-fn read_data(file: &str) -> HashMap<u32, Recipe> {
-    let reader = BufReader::new(File::open(file).expect("Cannot open"));
-    reader.lines()
-        .map(|l| l.unwrap().parse::<Recipe>().unwrap())
+fn parse(input: &str) -> HashMap<u32, Recipe> {
+    input.lines()
+        .map(|l| l.parse::<Recipe>().unwrap())
         .map(|r| (r.output.id, r))
         .collect()
 }
 
-fn resolve(recipes: &HashMap<u32, Recipe>, extras: &mut HashMap<u32, u64>, result: &Ingredient) -> Ingredient {
-    if result.id == Ingredient::ORE {
-        return *result;
-    }
+// The chemicals involved in producing the result, ordered so every chemical comes after everything
+// that consumes it
+fn order(recipes: &HashMap<u32, Recipe>, result: u32) -> Vec<u32> {
+    Reactions(recipes).topological_sort(&[result]).expect("Recipes cannot be circular")
+}
 
-    let recipe = recipes.get(&result.id).expect("Missing");
-    let mut ores = Ingredient { id: Ingredient::ORE, amount: 0 };
-    let mut amount_created = extras.remove(&result.id).unwrap_or(0);
-    if amount_created < result.amount {
-        let batches = div_ceil(result.amount-amount_created, recipe.output.amount);
-        while amount_created < result.amount {
-            for input in recipe.inputs.iter() {
-                ores += resolve(recipes, extras, &(*input * batches));
-            }
-            amount_created += recipe.output.amount * batches;
+// Walks the recipes in the result's order(), so the total amount of each chemical needed is known
+// before producing any of it.
+fn resolve(recipes: &HashMap<u32, Recipe>, order: &[u32], extras: &mut HashMap<u32, u64>, result: &Ingredient) -> Ingredient {
+    let mut needed = HashMap::new();
+    needed.insert(result.id, result.amount);
+
+    for &id in order.iter().filter(|&&id| id != Ingredient::ORE) {
+        let amount = needed.remove(&id).unwrap_or(0);
+        let recipe = recipes.get(&id).expect("Missing");
+        let extra = extras.remove(&id).unwrap_or(0);
+        let batches = div_ceil(amount.saturating_sub(extra), recipe.output.amount);
+        let amount_created = extra + recipe.output.amount * batches;
+        if amount_created > amount {
+            extras.insert(id, amount_created - amount);
+        }
+        for input in recipe.inputs.iter() {
+            *needed.entry(input.id).or_insert(0) += input.amount * batches;
         }
     }
-    if amount_created > result.amount {
-        extras.insert(result.id, amount_created - result.amount);
+    Ingredient { id: Ingredient::ORE, amount: needed.get(&Ingredient::ORE).cloned().unwrap_or(0) }
+}
+
+// Each chemical's edges lead to the chemicals its recipe consumes
+struct Reactions<'a>(&'a HashMap<u32, Recipe>);
+
+impl<'a> Graph for Reactions<'a> {
+    type Node = u32;
+    type Weight = i32;
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        self.0.get(source)
+            .map(|r| r.inputs.iter().map(|i| Edge::new(1, *source, i.id)).collect())
+            .unwrap_or_default()
     }
-    ores
 }
 
 fn maximize(recipes: &HashMap<u32, Recipe>, max_input: &Ingredient) -> Ingredient {
@@ -62,10 +84,11 @@ fn maximize(recipes: &HashMap<u32, Recipe>, max_input: &Ingredient) -> Ingredien
     let mut batch_size = 100000;
     let mut fuel_created = 0;
     let mut extras = HashMap::new();
+    let order = order(recipes, Ingredient::FUEL);
     loop {
         // TODO this is wrong! we mutate extras even if we throw out the computation
         // can increase the batch size once this is fixed
-        let ore = resolve(recipes, &mut extras, &Ingredient { id: Ingredient::FUEL, amount: batch_size }).amount as i64;
+        let ore = resolve(recipes, &order, &mut extras, &Ingredient { id: Ingredient::FUEL, amount: batch_size }).amount as i64;
         if ore > ore_left {
             if batch_size > 1 {
                 batch_size /= 10;
@@ -83,7 +106,7 @@ fn maximize(recipes: &HashMap<u32, Recipe>, max_input: &Ingredient) -> Ingredien
 
 // https://users.rust-lang.org/t/ceiling-of-isize-isize/23285/4
 fn div_ceil(n: u64, d: u64) -> u64 {
-    n/d + if n % d != 0 { 1 } else { 0 }
+    n/d + if !n.is_multiple_of(d) { 1 } else { 0 }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -157,6 +180,7 @@ impl FromStr for Recipe {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
     parameterized_test::create! { encode_name, (name, expected), {
         assert_eq!(Ingredient::encode_name(name), expected);
@@ -168,11 +192,11 @@ mod tests {
     }
 
     parameterized_test::create! { process_recipes, (file, ore_expected, fuel_expected), {
-        let recipes = read_data(&format!("data/day14-example{}.txt", file));
+        let recipes = parse(&input::read(&format!("day14-example{}.txt", file)));
         let fuel = Ingredient { id: Ingredient::FUEL, amount: 1 };
         let ore = Ingredient { id: Ingredient::ORE, amount: 1000000000000u64 };
 
-        assert_eq!(resolve(&recipes, &mut HashMap::new(), &fuel).amount, ore_expected);
+        assert_eq!(resolve(&recipes, &order(&recipes, fuel.id), &mut HashMap::new(), &fuel).amount, ore_expected);
         assert_eq!(maximize(&recipes, &ore).amount, fuel_expected);
     }}
     process_recipes! {
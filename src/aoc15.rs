@@ -1,13 +1,25 @@
 use crate::intcode::{Machine, State};
-use crate::euclid::{Point, point, Vector, vector};
-use std::collections::HashMap;
-use crate::pathfinding::{Graph, Edge};
+use crate::euclid::{Direction4, Grid, Point, SparseGrid};
+use crate::pathfinding::{manhattan, regions, Graph, Edge, SearchRenderer};
+use crate::puzzle::{Answer, Puzzle};
+use crate::console::{Color, Renderer};
 use std::fmt;
 
-pub fn advent() {
-    let map = Map::explore(Machine::from_file("data/day15.txt"));
-    println!("Distance to device: {}", map.distance_to_o2_system());
-    println!("Minutes for Oxygen to travel: {}", map.time_for_o2_to_spread());
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(15, parse, vec!(distance_to_device, minutes_for_oxygen))
+}
+
+fn parse(input: &str) -> Map {
+    Map::explore(Machine::from_program(input))
+}
+
+fn distance_to_device(map: &Map) -> Answer {
+    Answer::new("Distance to Device", map.distance_to_o2_system())
+}
+
+fn minutes_for_oxygen(map: &Map) -> Answer {
+    Answer::new("Minutes for Oxygen to Spread", map.time_for_o2_to_spread())
+        .with_detail("Area Filled", map.o2_area())
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -18,29 +30,30 @@ enum Type {
 }
 
 struct Map {
-    visited: HashMap<Point, Type>,
+    visited: SparseGrid<Type>,
     pos: Point,
-    dir: Dir,
+    dir: Direction4,
     device: Option<Point>,
 }
 
 impl Map {
     fn explore(mut machine: Machine) -> Map {
-        let mut map = Map { visited: HashMap::new(), pos: Point::ORIGIN, dir: Dir::North, device: None };
+        let mut map = Map { visited: SparseGrid::new(), pos: Point::ORIGIN, dir: Direction4::North, device: None };
+        let mut renderer = Renderer::new().record("day15-maze").palette(|c| if c == 'X' { Some(Color::Green) } else { None });
 
         loop {
-            machine.send_input(map.dir.command());
+            machine.send_input(command(map.dir));
             let state = machine.run();
             let output = machine.read_output();
             assert_eq!(output.len(), 1);
             match output[0] {
                 0 => {
                     map.visited.insert(map.pos + map.dir.vector(), Type::Wall);
-                    if let Some(Type::Wall) = map.visited.get(&(map.pos + map.dir.right().vector())) {
-                        if let Some(Type::Wall) = map.visited.get(&(map.pos + map.dir.left().vector())) {
+                    if let Some(Type::Wall) = map.visited.get(map.pos + map.dir.right().vector()) {
+                        if let Some(Type::Wall) = map.visited.get(map.pos + map.dir.left().vector()) {
                             map.dir = map.dir.left();
                         } else {
-                            map.dir = map.dir.flip();
+                            map.dir = map.dir.reverse();
                         }
                     } else {
                         map.dir = map.dir.right();
@@ -63,119 +76,82 @@ impl Map {
                 },
                 _ => panic!(),
             }
-            if interactive!() {
-                let image = map.to_string();
-                println!("{}\u{001B}[{}A", image, image.chars().filter(|&c| c == '\n').count() + 1);
-                //std::thread::sleep(std::time::Duration::from_millis(5));
-            }
+            renderer.draw(&map);
             match state {
                 State::Input => {}
                 State::Halt => { break; }
                 _ => panic!(),
             }
         }
-        if interactive!() {
-            println!("{}", map);
-        }
+        renderer.finish(&map);
 
         map
     }
 
     fn distance_to_o2_system(&self) -> u32 {
-        self.dijkstras(&Point::ORIGIN, |n| n == &self.device.expect("Device not found"))
-            .expect("No path").len() as u32
+        let device = self.device.expect("Device not found");
+        let renderer = Renderer::new().record("day15-search").palette(|c| match c {
+            SearchRenderer::FRONTIER => Some(Color::Yellow),
+            SearchRenderer::PATH => Some(Color::Green),
+            _ => None,
+        });
+        let mut search = SearchRenderer::new(renderer, |p| match self.visited.get(p) {
+            Some(Type::Wall) => '█',
+            _ => ' ',
+        });
+        let route = self.astar_observed(&Point::ORIGIN, |n| n == &device, manhattan(device), &mut search);
+        search.finish();
+        route.goal_cost().expect("No path") as u32
     }
 
+    // Each minute the oxygen fills one more layer outwards from the device
     fn time_for_o2_to_spread(&self) -> u32 {
-        self.bfs_all(&self.device.expect("Device not found"))
-            .values().map(|v| v.len()).max().expect("No routes") as u32 - 1
+        (self.flood_fill(&self.device.expect("Device not found")).len() - 1) as u32
+    }
+
+    // The number of open cells connected to the device, all of which the oxygen eventually fills
+    fn o2_area(&self) -> usize {
+        let device = self.device.expect("Device not found");
+        let (min, max) = self.visited.bounds().expect("Not explored");
+        let open = Grid::from_fn(min, (max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize,
+                                 |p| self.visited.get(p).is_some_and(|t| t != &Type::Wall));
+        regions(&open).into_iter().find(|r| r.contains(device)).expect("Device must be open").area()
     }
 }
 
 impl fmt::Display for Map {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let bounds = Point::bounding_box(self.visited.keys().cloned()).expect("No points");
-        let mut out = String::new();
-        for y in bounds.0.y..bounds.1.y+1 {
-            for x in bounds.0.x..bounds.1.x + 1 {
-                let coord = point(x, y);
-                use Type::*;
-                let c = match self.visited.get(&coord) {
-                    Some(Wall) => '█',
-                    Some(Hall) => ' ',
-                    Some(Device) => 'X',
-                    None => '░',
-                };
-                let c = if coord == self.pos { '#' } else { c };
-                out.push(c);
-            }
-            out.push('\n');
-        }
-        out.pop();
+        use Type::*;
+        let out = self.visited.render(|coord, t| match t {
+            _ if coord == self.pos => '#',
+            Some(Wall) => '█',
+            Some(Hall) => ' ',
+            Some(Device) => 'X',
+            None => '░',
+        });
         write!(f, "{}", out)
     }
 }
 
 impl Graph for Map {
     type Node = Point;
+    type Weight = i32;
 
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-        vec!(vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)).iter()
-            .map(|v| source + v)
-            .filter(|p| self.visited.get(p).unwrap_or(&Type::Wall) != &Type::Wall)
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        Direction4::ALL.iter()
+            .map(|d| source + d.vector())
+            .filter(|p| self.visited.get(*p).unwrap_or(&Type::Wall) != &Type::Wall)
             .map(|d| Edge::new(1, *source, d))
             .collect()
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-enum Dir {
-    North,
-    South,
-    West,
-    East,
-}
-
-impl Dir {
-    fn command(&self) -> i64 {
-        use Dir::*;
-        match self {
-            North => 1,
-            South => 2,
-            West => 3,
-            East => 4,
-        }
-    }
-
-    fn vector(&self) -> Vector {
-        use Dir::*;
-        match self {
-            North => vector(0, -1),
-            South => vector(0, 1),
-            West => vector(-1, 0),
-            East => vector(1, 0),
-        }
-    }
-
-    fn right(&self) -> Dir {
-        use Dir::*;
-        match self {
-            North => East,
-            South => West,
-            West => North,
-            East => South,
-        }
-    }
-
-    fn flip(&self) -> Dir {
-        use Dir::*;
-        match self {
-            North => South,
-            South => North,
-            West => East,
-            East => West,
-        }
+// The droid's movement commands
+fn command(dir: Direction4) -> i64 {
+    match dir {
+        Direction4::North => 1,
+        Direction4::South => 2,
+        Direction4::West => 3,
+        Direction4::East => 4,
     }
-
-    fn left(&self) -> Dir { self.flip().right() }
 }
\ No newline at end of file
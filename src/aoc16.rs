@@ -1,15 +1,20 @@
-pub fn advent() {
-    let input = read_data();
-    let sequence = to_vec(input.trim());
+use crate::puzzle::{Answer, Puzzle};
 
-    println!("FFT Test: {}", to_str(&fft(&sequence)[..8]));
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(16, parse, vec!(fft_test, fft_run))
+}
+
+fn parse(input: &str) -> Vec<u32> {
+    to_vec(input.trim())
+}
 
-    let offset: usize = input[..7].parse().expect("Invalid");
-    println!("FFT Run:  {}", to_str(&fast_fft(&repeat(&sequence), offset)));
+fn fft_test(sequence: &[u32]) -> Answer {
+    Answer::new("FFT Test", to_str(&fft(sequence)[..8]))
 }
 
-fn read_data() -> String {
-    std::fs::read_to_string("data/day16.txt").expect("Cannot open")
+fn fft_run(sequence: &[u32]) -> Answer {
+    let offset = sequence[..7].iter().fold(0, |acc, &d| acc * 10 + d as usize);
+    Answer::new("FFT Run", to_str(&fast_fft(&repeat(sequence), offset)))
 }
 
 fn to_vec(s: &str) -> Vec<u32> {
@@ -28,7 +33,7 @@ fn apply_pattern(input: &[u32], offset: usize, pattern: usize) -> u32 {
     for (i, &item) in input.iter().enumerate() {
         sum += item as i32 * pattern_at_index(i+offset, pattern);
     }
-    sum.abs() as u32 % 10
+    sum.unsigned_abs() % 10
 }
 
 const BASE_PATTERN: [i32; 4] = [0, 1, 0, -1];
@@ -1,23 +1,34 @@
 use crate::intcode::{Machine, State};
-use crate::euclid::{point, Point, vector, Vector};
-use std::collections::HashSet;
+use crate::euclid::{Direction4, Grid, Heading, Point, Turn};
+use std::convert::TryFrom;
 use std::fmt::Write;
 use regex::Regex;
+use crate::puzzle::{Answer, Puzzle};
+use crate::console::{Color, Renderer};
 
-pub fn advent() {
-    let image = read_data();
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(17, Machine::from_program, vec!(alignment_parameters, dust_collected))
+}
+
+fn camera_view(image: &Machine) -> String {
     let mut machine = image.clone();
     machine.run().assert_halt();
-    let display = machine.read_output_ascii();
+    machine.read_output_ascii()
+}
 
-    let (start, points) = plot_map(&display);
-    println!("Alignment Parameters: {}", intersection_points_sum(&points));
+fn alignment_parameters(image: &Machine) -> Answer {
+    let (_, points) = plot_map(&camera_view(image));
+    Answer::new("Alignment Parameters", intersection_points_sum(&points))
+}
 
+fn dust_collected(image: &Machine) -> Answer {
+    let display = camera_view(image);
+    let (start, points) = plot_map(&display);
     let path = gen_path(start, &points);
 
     let (comp,a,b,c) = compress(&path).expect("Encoding not found");
 
-    let mut machine = image;
+    let mut machine = image.clone();
     machine.set_state(0, 2);
 
     machine.run().assert_input();
@@ -40,7 +51,11 @@ pub fn advent() {
 
     machine.run().assert_input();
     assert_eq!(machine.read_output_ascii(), "Continuous video feed?\n");
-    let debug = if interactive!() { 'y' } else { 'n' };
+    let mut renderer = Renderer::new().record("day17-video").palette(|c| match c {
+        '^' | 'v' | '<' | '>' => Some(Color::Yellow),
+        _ => None,
+    });
+    let debug = if renderer.enabled() { 'y' } else { 'n' };
     machine.send_input_ascii(&format!("{}\n", debug));
 
     if debug == 'y' {
@@ -48,118 +63,67 @@ pub fn advent() {
         assert_eq!(machine.run_until(|o| o == ['\n' as i64]), State::Output);
         assert_eq!(machine.read_output_ascii(), "\n");
 
-        let mut display = display;
+        let mut frame = String::new();
         loop {
             match machine.run_until(|o| o.len() >= 2 && o[o.len()-2..] == ['\n' as i64, '\n' as i64]) {
                 State::Output => {
-                    display = machine.read_output_ascii();
-                    let display = &display[..display.len()-1]; // remove the extra trailing newline
-                    print!("{}\u{001B}[{}A", display.replace('.', " "), display.chars().filter(|&c| c=='\n').count());
+                    frame = machine.read_output_ascii().replace('.', " ");
+                    renderer.draw(&frame);
                 },
-                State::Halt => { println!("{}", display.replace('.', " ")); break; }
+                State::Halt => { renderer.finish(frame); break; }
                 _ => panic!(),
             }
         }
     } else {
         machine.run().assert_halt();
     }
-    println!("Dust collected: {}", machine.read_output().last().expect("No output remaining"));
+    Answer::new("Dust Collected", machine.read_output().last().expect("No output remaining"))
 }
 
-fn read_data() -> Machine {
-    Machine::from_file("data/day17.txt")
+fn plot_map(input: &str) -> (Heading, Grid<bool>) {
+    let cells = Grid::parse(input, |c| match c {
+        '#'|'^'|'v'|'<'|'>'|'.' => Some(c),
+        _ => None,
+    }).expect("Invalid camera view");
+    let start = cells.iter()
+        .find_map(|(p, &c)| Direction4::try_from(c).ok().map(|d| Heading::new(p, d)))
+        .expect("Robot not found");
+    let points = Grid::from_fn(Point::ORIGIN, cells.width(), cells.height(), |p| cells[p] != '.');
+    (start, points)
 }
 
-fn plot_map(input: &str) -> (Point,HashSet<Point>) {
-    let mut pos = Point::ORIGIN;
-    let mut start = None;
-    let mut points = HashSet::new();
-    for c in input.chars() {
-        match c {
-            '#'|'^'|'v'|'<'|'>' => { points.insert(pos); if c != '#' { start = Some(pos) } },
-            '.' => {},
-            '\n' => { pos = point(-1, pos.y+1); },
-            _ => panic!(),
-        };
-        pos += vector(1, 0);
-    }
-    (start.expect("Robot not found"), points)
+fn scaffold(points: &Grid<bool>, p: Point) -> bool {
+    points.get(p).cloned().unwrap_or(false)
 }
 
-fn intersection_points_sum(points: &HashSet<Point>) -> i32 {
-    points.iter().filter(|&p|
+fn intersection_points_sum(points: &Grid<bool>) -> i32 {
+    points.iter().filter(|&(p, &s)|
             // Only count four-way intersections, since we know the scaffold forms a path there
             // shouldn't be any T-junctions or other intersection types
-            points.contains(&(p + vector(-1, 0))) &&
-            points.contains(&(p + vector(1, 0))) &&
-            points.contains(&(p + vector(0, 1))) &&
-            points.contains(&(p + vector(0, -1))))
-        .map(|&p| p.x * p.y)
+            s && points.neighbors4(p).filter(|&n| points[n]).count() == 4)
+        .map(|(p, _)| p.x * p.y)
         .sum()
 }
 
-fn gen_path(start: Point, points: &HashSet<Point>) -> String {
+fn gen_path(start: Heading, points: &Grid<bool>) -> String {
     let mut out = String::new();
-    let mut pos = start;
-    // TODO this assumes the robot starts facing-up, which may not always be true
-    let mut dir = vector(0, -1);
+    let mut robot = start;
     // TODO this assumes the robot starts facing sideways to the path, which may not always be true
-    while let Some(next) = find_dir(pos, dir, points) {
-        write!(out, "{},", to_letter(dir, next)).unwrap();
-        dir = next;
+    while let Some(&turn) = [Turn::Left, Turn::Right].iter()
+            .find(|&&t| scaffold(points, robot.pos + robot.dir.turn(t).vector())) {
+        write!(out, "{},", turn).unwrap();
+        robot.turn(turn);
         let mut dist = 0;
-        loop {
-            let next = pos + dir;
-            if points.contains(&next) {
-                dist += 1;
-                pos = next;
-            } else {
-                write!(out, "{},", dist).unwrap();
-                break;
-            }
+        while scaffold(points, robot.ahead()) {
+            dist += 1;
+            robot.advance(1);
         }
+        write!(out, "{},", dist).unwrap();
     }
     out.pop();
     out
 }
 
-fn find_dir(pos: Point, cur_dir: Vector, points: &HashSet<Point>) -> Option<Vector> {
-    let ew = &[vector(-1,0), vector(1, 0)];
-    let ns = &[vector(0, -1), vector(0, 1)];
-
-    for v in if cur_dir.x != 0 {ns} else {ew} {
-        if points.contains(&(pos + v)) { return Some(*v); }
-
-    }
-    None
-}
-
-fn to_letter(vec: Vector, next: Vector) -> char {
-    match vec {
-        Vector{x:-1, y:0} => match next {
-            Vector{x:0, y:-1} => 'R',
-            Vector{x:0, y:1} => 'L',
-            _ => panic!(),
-        },
-        Vector{x:1, y:0} => match next {
-            Vector{x:0, y:-1} => 'L',
-            Vector{x:0, y:1} => 'R',
-            _ => panic!(),
-        },
-        Vector{x:0, y:-1} => match next {
-            Vector{x:-1, y:0} => 'L',
-            Vector{x:1, y:0} => 'R',
-            _ => panic!(),
-        },
-        Vector{x:0, y:1} => match next {
-            Vector{x:-1, y:0} => 'R',
-            Vector{x:1, y:0} => 'L',
-            _ => panic!(),
-        },
-        _ => panic!(),
-    }
-}
-
 // Compresses a comma-separated string into groups of three substrings, labeled A, B, and C.
 // The resulting string is fully compressed (only 'A', 'B', 'C', and ',' will be left), and the
 // subsequent three returned values are the A, B, and C expansions.
@@ -235,7 +199,7 @@ mod tests {
             #############\n\
             ..#...#...#..\n\
             ..#####...^..";
-        let (_, map) = plot_map(&image);
+        let (_, map) = plot_map(image);
         assert_eq!(intersection_points_sum(&map), 76);
     }
 
@@ -256,7 +220,7 @@ mod tests {
             ....#...#......\n\
             ....#...#......\n\
             ....#####......";
-        let (start, map) = plot_map(&image);
+        let (start, map) = plot_map(image);
         let path = gen_path(start, &map);
         let (main, a, b, c) = compress(&path).unwrap();
         // This isn't the same output as the example, though it appears to also be valid
@@ -1,25 +1,39 @@
 use std::collections::HashMap;
+use crate::console;
 use crate::euclid::{point, Point, vector};
 use std::str::FromStr;
 use crate::error::ParseError;
 use std::fmt;
-use crate::pathfinding::{Graph, Edge};
+use crate::pathfinding::{AdjacencyGraph, Graph, Edge, SearchObserver, SearchStats};
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    // https://old.reddit.com/r/adventofcode/comments/ednz2o/2019_day_18_for_dummies/fbk1qg3/
-    let map = read_data();
-    if interactive!() {
-        println!("{}", map);
-    }
+// https://old.reddit.com/r/adventofcode/comments/ednz2o/2019_day_18_for_dummies/fbk1qg3/
+pub fn puzzle() -> Puzzle {
+    Puzzle::interactive(18, parse, vec!(scouted_route, robots_route), display)
+}
 
-    println!("Scouted Route: {}", map.route_len());
+fn parse(input: &str) -> Map {
+    input.parse().expect("Invalid")
+}
 
-    let robo_map = RoboMap::create(&map);
-    println!("Robots' Route: {}", robo_map.route_len());
+fn display(map: &Map) -> bool {
+    console::write(format!("{}\n", map));
+    false
 }
 
-fn read_data() -> Map {
-    std::fs::read_to_string("data/day18.txt").expect("Not found").parse().expect("Invalid")
+fn scouted_route(map: &Map) -> Answer {
+    let mut stats = SearchStats::default();
+    Answer::new("Scouted Route", map.route_len_observed(&mut stats)).with_note(stats)
+}
+
+fn robots_route(map: &Map) -> Answer {
+    let map = RoboMap::create(map);
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if threads > 1 {
+        return Answer::new("Robots' Route", map.parallel_route_len(threads));
+    }
+    let mut stats = SearchStats::default();
+    Answer::new("Robots' Route", map.route_len_observed(&mut stats)).with_note(stats)
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -70,36 +84,64 @@ struct Map {
     coords: HashMap<Point, Type>,
     entrance: Point,
     keys: HashMap<char, Point>,
+    corridors: AdjacencyGraph<Point, i32>,
 }
 
 impl Map {
+    #[cfg(test)]
     fn route_len(&self) -> usize {
+        self.route_len_observed(&mut ())
+    }
+
+    fn route_len_observed(&self, observer: &mut impl SearchObserver<ScanState, i32>) -> usize {
         let goal = CharSet::create(&self.keys.keys().collect::<String>());
-        self.bfs(&ScanState::create(self.entrance, CharSet::create("")), |n| n.keys == goal)
-            .expect("No route").len() - 1
+        let route = self.dijkstras_observed(&ScanState::create(self.entrance, CharSet::create("")), |n| n.keys == goal, observer);
+        route.goal_cost().expect("No route") as usize
     }
 }
 
 impl Graph for Map {
     type Node = ScanState;
+    type Weight = i32;
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        self.corridors.neighbors(&source.pos).iter()
+            .filter_map(|edge| {
+                let p = *edge.dest();
+                let next = match self.coords[&p] {
+                    Type::Key(k) => source.moved_to(p).found_key(k),
+                    Type::Door(d) if !source.keys.contains(d) => return None,
+                    _ => source.moved_to(p),
+                };
+                Some(Edge::new(edge.weight(), *source, next))
+            })
+            .collect()
+    }
+}
 
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
+// The open cells of the vault, ignoring doors
+struct Vault<'a>(&'a HashMap<Point, Type>);
+
+impl<'a> Graph for Vault<'a> {
+    type Node = Point;
+    type Weight = i32;
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
         [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
-            .map(|v| source.pos + v)
-            .filter_map(|p| {
-                let next = source.moved_to(p);
-                match self.coords.get(&p) {
-                    Some(Type::Key(k)) =>  Some(next.found_key(*k)),
-                    Some(Type::Hall) => Some(next),
-                    Some(Type::Door(d)) => if source.keys.contains(*d) { Some(next) } else { None },
-                    _ => None,
-                }
-            })
-            .map(|d| Edge::new(1, *source, d))
+            .map(|v| source + v)
+            .filter(|p| !matches!(self.0.get(p), None|Some(Type::Wall)))
+            .map(|p| Edge::new(1, *source, p))
             .collect()
     }
 }
 
+// Only the entrances, keys, and doors matter to the search, the halls between them (other than
+// their length) don't, so they're compressed into single edges.
+fn corridors(coords: &HashMap<Point, Type>, entrances: &[Point]) -> AdjacencyGraph<Point, i32> {
+    AdjacencyGraph::explore(&Vault(coords), entrances)
+        .compress(|p| entrances.contains(p) || matches!(coords[p], Type::Key(_)|Type::Door(_)))
+}
+
 impl FromStr for Map {
     type Err = ParseError;
 
@@ -128,7 +170,9 @@ impl FromStr for Map {
             coord += vector(1, 0);
         }
 
-        Ok(Map { coords, entrance: entrance.expect("No entrance found."), keys, })
+        let entrance = entrance.expect("No entrance found.");
+        let corridors = corridors(&coords, &[entrance]);
+        Ok(Map { coords, entrance, keys, corridors })
     }
 }
 
@@ -162,7 +206,8 @@ impl fmt::Display for Map {
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 struct RoboState {
     pos: [Point; 4],
-    active: Option<usize>,
+    // A u8 (rather than usize) keeps the struct small, there are millions of these in the search
+    active: Option<u8>,
     keys: CharSet,
 }
 
@@ -172,13 +217,13 @@ impl RoboState {
     }
 
     fn create(pos: [Point; 4], active: usize, keys: CharSet) -> RoboState {
-        RoboState { pos, active: Some(active), keys }
+        RoboState { pos, active: Some(active as u8), keys }
     }
 
     fn moved_to(&self, point: Point) -> RoboState {
         let mut pos = self.pos;
-        pos[self.active.expect("Must be active")] = point;
-        RoboState::create(pos, self.active.expect("Must be active"), self.keys)
+        pos[self.active.expect("Must be active") as usize] = point;
+        RoboState::create(pos, self.active.expect("Must be active") as usize, self.keys)
     }
 
     fn found_key(&self, key: char) -> Vec<RoboState> {
@@ -197,6 +242,7 @@ struct RoboMap {
     coords: HashMap<Point, Type>,
     entrances: [Point; 4],
     keys: HashMap<char, Point>,
+    corridors: AdjacencyGraph<Point, i32>,
 }
 
 impl RoboMap {
@@ -208,50 +254,54 @@ impl RoboMap {
         let e = map.entrance;
         let entrances = [e + vector(-1, -1), e + vector(1, -1), e + vector(-1, 1), e + vector(1, 1)];
 
-        RoboMap { coords, entrances, keys: map.keys.clone() }
+        let corridors = corridors(&coords, &entrances);
+        RoboMap { coords, entrances, keys: map.keys.clone(), corridors }
     }
 
+    #[cfg(test)]
     fn route_len(&self) -> i32 {
+        self.route_len_observed(&mut ())
+    }
+
+    fn route_len_observed(&self, observer: &mut impl SearchObserver<RoboState, i32>) -> i32 {
+        let goal = CharSet::create(&self.keys.keys().collect::<String>());
+        let route = self.dijkstras_observed(&RoboState::initial(self.entrances), |n| n.keys == goal, observer);
+        route.goal_cost().expect("No route")
+    }
+
+    // Expands states within 20 steps of the cheapest together, which was the fastest delta on
+    // the real input. Unlike route_len_observed() the search can't report stats.
+    fn parallel_route_len(&self, threads: usize) -> i32 {
         let goal = CharSet::create(&self.keys.keys().collect::<String>());
-        self.bfs(&RoboState::initial(self.entrances), |n| n.keys == goal)
-            .expect("No route").len() as i32 - 2 // 2, start and "initial", see below
+        let route = self.parallel_dijkstras(&RoboState::initial(self.entrances), |n| n.keys == goal, 20, threads);
+        route.goal_cost().expect("No route")
     }
 }
 
 impl Graph for RoboMap {
     type Node = RoboState;
+    type Weight = i32;
 
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
         if source.active.is_none() { // from RoboState::initial()
             return (0..source.pos.len())
                 .map(|i| RoboState::create(source.pos, i, source.keys))
-                // it would be more correct for these edges to be zero-weight and use dijkstras, but
-                // doing so incurs a significant overhead. Since exactly one of these nodes will
-                // appear at the start any valid path (and nowhere else) it's simple enough to just
-                // exclude the extra node from the result.
-                .map(|d| Edge::new(1, *source, d))
+                // choosing which robot moves first doesn't take a step
+                .map(|d| Edge::new(0, *source, d))
                 .collect();
         }
 
-        [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
-            .map(|v| source.pos[source.active.expect("Must be active")] + v)
-            .map(|p| (p, self.coords.get(&p)))
-            .filter_map(|(p, t)|
-                match t {
-                    None|Some(Type::Wall) => None,
-                    Some(t) => Some((p, *t))
-                }
-            )
-            .flat_map(|(p, t)| {
+        self.corridors.neighbors(&source.pos[source.active.expect("Must be active") as usize]).iter()
+            .flat_map(|edge| {
+                let p = *edge.dest();
                 let next = source.moved_to(p);
-                match t {
+                let states = match self.coords[&p] {
                     Type::Key(k) => if source.keys.contains(k) { vec!(next) } else { next.found_key(k) },
                     Type::Door(d) => if source.keys.contains(d) { vec!(next) } else { vec!() },
-                    Type::Hall => vec!(next),
-                    _ => unreachable!(),
-                }
+                    _ => vec!(next),
+                };
+                states.into_iter().map(move |d| Edge::new(edge.weight(), *source, d))
             })
-            .map(|d| Edge::new(1, *source, d))
             .collect()
     }
 }
@@ -286,7 +336,7 @@ impl CharSet {
 
     fn to_mask(c: char) -> u32 {
         let c = c.to_ascii_uppercase();
-        assert!(('A'..='Z').contains(&c));
+        assert!(c.is_ascii_uppercase());
         let idx = (c as u8 - b'A') as u32;
         1 << idx
     }
@@ -307,6 +357,59 @@ impl fmt::Debug for CharSet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pathfinding::StopReason;
+
+    // The states of a search that track which keys have been collected
+    trait Collector {
+        fn keys(&self) -> CharSet;
+
+        // An equivalent state from which every possible move can be made next
+        fn idle(&self) -> Self;
+    }
+
+    impl Collector for ScanState {
+        fn keys(&self) -> CharSet { self.keys }
+
+        fn idle(&self) -> Self { *self }
+    }
+
+    impl Collector for RoboState {
+        fn keys(&self) -> CharSet { self.keys }
+
+        fn idle(&self) -> Self { RoboState { active: None, ..*self } }
+    }
+
+    // Moves straight from key to key, rather than step by step, so every edge collects another key.
+    // Unlike the underlying graph this one is acyclic, which memoized_dfs() requires. Each search
+    // starts from the idle() state, so states that only differ in which robot is active are
+    // interchangeable.
+    struct KeyRoutes<'a, G: Graph>(&'a G);
+
+    impl<'a, G: Graph> Graph for KeyRoutes<'a, G> where G::Node: Collector {
+        type Node = G::Node;
+        type Weight = G::Weight;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            let keys = source.keys();
+            UntilNewKey(self.0, keys).dijkstras_all(&source.idle()).costs()
+                .filter(|(n, _)| n.keys() != keys)
+                .map(|(n, cost)| Edge::new(cost, source.clone(), n.clone()))
+                .collect()
+        }
+    }
+
+    // The underlying graph, stopping at any state that has collected a key not in the given set
+    struct UntilNewKey<'a, G: Graph>(&'a G, CharSet);
+
+    impl<'a, G: Graph> Graph for UntilNewKey<'a, G> where G::Node: Collector {
+        type Node = G::Node;
+        type Weight = G::Weight;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            if source.keys() != self.1 { return vec!(); }
+            self.0.neighbors(source)
+        }
+    }
 
     const EXAMPLE_1: &str = "\
         #########\n\
@@ -411,6 +514,90 @@ mod tests {
         d: (EXAMPLE_9, 72),
     }
 
+    parameterized_test::create!{ key_routes, text, {
+        let map: Map = text.parse().unwrap();
+        let start = ScanState::create(map.entrance, CharSet::create(""));
+        let goal = CharSet::create(&map.keys.keys().collect::<String>());
+        let expected = map.dijkstras(&start, |n| n.keys == goal).goal_cost();
+
+        let routes = KeyRoutes(&map);
+        let memoized = routes.memoized_dfs(&start, |n| n.keys == goal, |n| *n);
+        assert_eq!(memoized.goal_cost(), expected);
+        assert_eq!(memoized.goal_path().unwrap().len(), map.keys.len());
+        // DFS finds a route, but not necessarily the shortest one
+        assert!(routes.dfs(&start, |n| n.keys == goal, |n| *n).goal_cost() >= expected);
+    }}
+    key_routes!{
+        a: EXAMPLE_1,
+        b: EXAMPLE_2,
+        c: EXAMPLE_3,
+        d: EXAMPLE_4,
+        e: EXAMPLE_5,
+    }
+
+    // Without memoisation IDDFS tries every order of collecting the keys, so only small examples
+    parameterized_test::create!{ iddfs_key_routes, text, {
+        let map: Map = text.parse().unwrap();
+        let start = ScanState::create(map.entrance, CharSet::create(""));
+        let goal = CharSet::create(&map.keys.keys().collect::<String>());
+        let expected = map.dijkstras(&start, |n| n.keys == goal).goal_cost();
+
+        // Every route has one edge per key, so IDDFS finds a route, but not necessarily the shortest
+        let routes = KeyRoutes(&map);
+        let iddfs = routes.iddfs(&start, |n| n.keys == goal, map.keys.len(), |n| *n);
+        assert_eq!(iddfs.goal_path().unwrap().len(), map.keys.len());
+        assert!(iddfs.goal_cost() >= expected);
+        let too_shallow = routes.iddfs(&start, |n| n.keys == goal, map.keys.len() - 1, |n| *n);
+        assert_eq!((too_shallow.goal(), too_shallow.stop_reason()), (None, StopReason::MaxDepth));
+    }}
+    iddfs_key_routes!{
+        a: EXAMPLE_1,
+        b: EXAMPLE_2,
+        c: EXAMPLE_3,
+    }
+
+    parameterized_test::create!{ robo_key_routes, text, {
+        let map = RoboMap::create(&text.parse().unwrap());
+        let start = RoboState::initial(map.entrances);
+        let goal = CharSet::create(&map.keys.keys().collect::<String>());
+
+        let expected = map.dijkstras(&start, |n| n.keys == goal).goal_cost();
+        assert_eq!(Some(map.route_len()), expected);
+    }}
+    robo_key_routes!{
+        a: EXAMPLE_6,
+        b: EXAMPLE_7,
+        c: EXAMPLE_8,
+        d: EXAMPLE_9,
+    }
+
+    parameterized_test::create!{ parallel_robo_routes, (text, threads), {
+        let map = RoboMap::create(&text.parse().unwrap());
+        let start = RoboState::initial(map.entrances);
+        let goal = CharSet::create(&map.keys.keys().collect::<String>());
+
+        let expected = map.dijkstras(&start, |n| n.keys == goal).goal_cost();
+        for delta in &[0, 1, 5, 100] {
+            assert_eq!(map.parallel_dijkstras(&start, |n| n.keys == goal, *delta, threads).goal_cost(), expected);
+        }
+        let routes = KeyRoutes(&map);
+        assert_eq!(routes.parallel_dijkstras(&start, |n| n.keys == goal, 10, threads).goal_cost(), expected);
+    }}
+    #[test]
+    #[ignore] // Slow: each search takes several seconds on the real input
+    fn parallel_real_route() {
+        let map = RoboMap::create(&crate::input::read("day18.txt").parse().unwrap());
+        assert_eq!(map.parallel_route_len(4), map.route_len());
+    }
+
+    parallel_robo_routes!{
+        a: (EXAMPLE_6, 4),
+        b: (EXAMPLE_7, 4),
+        c: (EXAMPLE_8, 4),
+        d: (EXAMPLE_9, 4),
+        single_threaded: (EXAMPLE_9, 1),
+    }
+
     const TWO: u32 = 2;
 
     parameterized_test::create!{ charsets, (letters, expected), {
@@ -1,18 +1,31 @@
 use crate::intcode::Machine;
 use crate::euclid::{point,Point};
 use std::collections::HashSet;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(19, Machine::from_program, vec!(traction_within_50, nearest_100_square))
+}
+
+fn traction_within(image: &Machine, size: i32) -> HashSet<Point> {
     let mut traction = HashSet::new();
-    for y in 0..50 {
-        for x in 0..50 {
+    for y in 0..size {
+        for x in 0..size {
             let coord = point(x,y);
-            if in_traction(coord) {
+            if in_traction(image, coord) {
                 traction.insert(coord);
             }
         }
     }
-    println!("Coords in traction within 50x50: {}", traction.len());
+    traction
+}
+
+fn traction_within_50(image: &Machine) -> Answer {
+    Answer::new("Coords in Traction Within 50x50", traction_within(image, 50).len())
+}
+
+fn nearest_100_square(image: &Machine) -> Answer {
+    let traction = traction_within(image, 50);
 
     // The tractor beam is tricksy; it has no traction, other than at the origin, within the first
     // several squares of the beam. The beam only gets "wide" enough to be detected further away.
@@ -23,7 +36,7 @@ pub fn advent() {
 
     let target_width = 100;
     loop {
-        widths.push(width_for(widths.len(), widths[widths.len()-1]));
+        widths.push(width_for(image, widths.len(), widths[widths.len()-1]));
         let lower = widths[widths.len()-1];
         if lower.1-lower.0+1 >= target_width {
             let upper = widths[widths.len()-target_width];
@@ -31,19 +44,18 @@ pub fn advent() {
                 let bounds = (
                     point(lower.0 as i32, (widths.len()-target_width) as i32),
                     point(upper.1 as i32, (widths.len()-1) as i32));
-                println!("{}x{0} rectangle found at {} -> {}", target_width, bounds.0, bounds.1);
-                println!("Identifier for nearest coord: {}", (bounds.0.x*10000)+bounds.0.y);
-                break;
+                return Answer::new("Identifier for Nearest Coord", (bounds.0.x*10000)+bounds.0.y)
+                    .with_detail("Rectangle", format!("{} -> {}", bounds.0, bounds.1));
             }
         }
     }
 }
 
-fn width_for(y: usize, prior: (usize, usize)) -> (usize, usize) {
+fn width_for(image: &Machine, y: usize, prior: (usize, usize)) -> (usize, usize) {
     //println!("Checking {} in range {:?}", y, prior);
     let mut min_x = None;
     for x in (0..prior.0+2).rev() {
-        if in_traction(point(x as i32, y as i32)) {
+        if in_traction(image, point(x as i32, y as i32)) {
             min_x = Some(x);
         } else if min_x.is_some() { break; }
     }
@@ -51,7 +63,7 @@ fn width_for(y: usize, prior: (usize, usize)) -> (usize, usize) {
 
     let mut max_x = None;
     for x in prior.1-1.. {
-        if in_traction(point(x as i32, y as i32)) {
+        if in_traction(image, point(x as i32, y as i32)) {
             max_x = Some(x);
         } else if max_x.is_some() { break; }
     }
@@ -60,11 +72,8 @@ fn width_for(y: usize, prior: (usize, usize)) -> (usize, usize) {
     (min_x, max_x)
 }
 
-fn in_traction(coord: Point) -> bool {
-    lazy_static!{
-        static ref IMAGE: Machine = Machine::from_file("data/day19.txt");
-    }
-    let mut machine = IMAGE.clone();
+fn in_traction(image: &Machine, coord: Point) -> bool {
+    let mut machine = image.clone();
     // TODO the algorithm above is sensitive to the fact that our beam is narrow and pointed
     // downward (i.e. expands slowly in the x relative to the y); flipping the x/y here ought to
     // be OK, but it causes this algorithm to crash.
@@ -80,6 +89,6 @@ mod tests {
 
     #[test]
     fn machine() {
-        assert!(in_traction(Point::ORIGIN));
+        assert!(in_traction(&Machine::from_input("day19.txt"), Point::ORIGIN));
     }
 }
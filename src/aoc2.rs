@@ -1,23 +1,24 @@
 use crate::intcode::Machine;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let image = read_data();
-    println!("Alarm State Pos 0: {}", run_program(image.clone(), 12, 2));
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(2, Machine::from_program, vec!(alarm_state, find_noun_verb))
+}
+
+fn alarm_state(image: &Machine) -> Answer {
+    Answer::new("Alarm State Pos 0", run_program(image.clone(), 12, 2))
+}
 
+fn find_noun_verb(image: &Machine) -> Answer {
     for noun in 0..100 {
         for verb in 0..100 {
             let output = run_program(image.clone(), noun, verb);
             if output == 19690720 {
-                println!("Found NounVerb: {}", noun * 100 + verb);
-                return;
+                return Answer::new("Found NounVerb", noun * 100 + verb);
             }
         }
     }
-    println!("No match!");
-}
-
-fn read_data() -> Machine {
-    Machine::from_file("data/day2.txt")
+    panic!("No match!");
 }
 
 fn run_program(mut machine: Machine, noun: i64, verb: i64) -> i64 {
@@ -39,6 +40,6 @@ mod tests {
 
     #[test]
     fn read_file() {
-        read_data();
+        Machine::from_input("day2.txt");
     }
 }
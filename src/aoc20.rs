@@ -1,20 +1,26 @@
 use std::collections::{HashMap, HashSet};
 use crate::euclid::{point,Point,vector};
-use crate::pathfinding::{Graph, Edge};
+use crate::euclidn::PointN;
+use crate::pathfinding::{AdjacencyGraph, Graph, Edge, ReversibleGraph, SearchLimits, SearchResult};
 use std::str::FromStr;
 use crate::error::ParseError;
 use std::collections::hash_map::Entry;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let maze = read_data("data/day20.txt");
-    println!("Distance: {}", maze.route().len());
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(20, parse, vec!(distance, recursive_distance))
+}
+
+fn parse(input: &str) -> Maze {
+    input.parse().expect("Invalid maze")
+}
 
-    let rec_maze = RecursiveMaze::new(&maze);
-    println!("Recursive Distance: {}", rec_maze.route().len());
+fn distance(maze: &Maze) -> Answer {
+    Answer::new("Distance", maze.distance())
 }
 
-fn read_data(file: &str) -> Maze {
-    std::fs::read_to_string(file).expect("Cannot open").parse().expect("Invalid maze")
+fn recursive_distance(maze: &Maze) -> Answer {
+    Answer::new("Recursive Distance", RecursiveMaze::new(maze).distance())
 }
 
 #[derive(Debug)]
@@ -38,8 +44,8 @@ impl Maze {
         }
     }
 
-    fn route(&self) -> Vec<Edge<Point>> {
-        self.dijkstras(&self.start, |n| n == &self.goal).expect("No path!")
+    fn distance(&self) -> i32 {
+        self.bidirectional_bfs(&self.start, &self.goal).goal_cost().expect("No path!")
     }
 }
 
@@ -104,50 +110,85 @@ impl FromStr for Maze {
 
 impl Graph for Maze {
     type Node = Point;
+    type Weight = i32;
 
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-        let direct: Vec<_> = [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
-            .map(|v| source + v)
-            .filter(|p| self.points.get(p).is_some())
-            .collect();
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        let portal = self.portals.get(source).map(|p| Edge::new(1, *source, *p));
+        Halls(self).neighbors(source).into_iter().chain(portal).collect()
+    }
+}
 
-        let portal = self.portals.get(source);
+// The maze without its portals
+struct Halls<'a>(&'a Maze);
 
-        direct.iter().chain(portal.iter().cloned())
-            .map(|p| Edge::new(1, *source, *p))
+impl<'a> Graph for Halls<'a> {
+    type Node = Point;
+    type Weight = i32;
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
+            .map(|v| source + v)
+            .filter(|p| self.0.points.contains(p))
+            .map(|p| Edge::new(1, *source, p))
             .collect()
     }
 }
 
+// Halls and portals can be traversed in either direction
+impl ReversibleGraph for Maze {
+    fn predecessors(&self, dest: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        self.neighbors(dest).iter().map(Edge::reversed).collect()
+    }
+}
+
 struct RecursiveMaze<'a> {
     maze: &'a Maze,
+    // The halls between portals, which are the same on every layer
+    corridors: AdjacencyGraph<Point, i32>,
 }
 
 impl<'a> RecursiveMaze<'a> {
     fn new(maze: &'a Maze) -> RecursiveMaze<'a> {
-        RecursiveMaze { maze }
+        let mut ends: Vec<_> = maze.portals.keys().cloned().collect();
+        ends.extend(&[maze.start, maze.goal]);
+        let corridors = AdjacencyGraph::explore(&Halls(maze), &ends).compress(|p| ends.contains(p));
+        RecursiveMaze { maze, corridors }
+    }
+
+    // The maze is infinitely deep, so rather than search forever give up after this many steps
+    const MAX_EXPANSIONS: usize = 1_000_000;
+
+    fn distance(&self) -> i32 {
+        let route = self.search(SearchLimits::default().max_expansions(RecursiveMaze::MAX_EXPANSIONS));
+        route.goal_cost().unwrap_or_else(|| panic!("No path! ({:?})", route.stop_reason()))
     }
 
-    fn route(&self) -> Vec<Edge<(Point, i32)>> {
-        self.dijkstras(&(self.maze.start, 0), |n| n == &(self.maze.goal, 0)).expect("No path!")
+    fn search(&self, mut limits: SearchLimits<i32>) -> SearchResult<PointN<3>, i32> {
+        let goal = PointN::from((self.maze.goal, 0));
+        self.astar_observed(&PointN::from((self.maze.start, 0)), |n| n == &goal, RecursiveMaze::heuristic, &mut limits)
+    }
+
+    // Returning to the outermost layer takes at least one (single-step) portal per layer
+    fn heuristic(node: &PointN<3>) -> i32 {
+        node[2]
     }
 }
 
 impl<'a> Graph for RecursiveMaze<'a> {
-    type Node = (Point, i32);
+    // (x, y, depth)
+    type Node = PointN<3>;
+    type Weight = i32;
 
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-        assert!(source.1 < 500, "No path within 500 layers");
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        let depth = source[2];
 
-        let portal = self.maze.portal(&source.0)
-            .filter(|(_, d)| source.1 + *d >= 0)
-            .map(|(dest, depth)| Edge::new(1, *source, (dest, source.1 + depth)));
+        let portal = self.maze.portal(&point(source[0], source[1]))
+            .filter(|(_, d)| depth + *d >= 0)
+            .map(|(dest, d)| Edge::new(1, *source, PointN::from((dest, depth + d))));
 
-        [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
-            .map(|v| source.0 + v)
-            .filter(|p| self.maze.points.get(p).is_some())
-            .map(|p| Edge::new(1, *source, (p, source.1)))
-            .chain(portal.into_iter())
+        self.corridors.neighbors(&point(source[0], source[1])).iter()
+            .map(|e| Edge::new(e.weight(), *source, PointN::from((*e.dest(), depth))))
+            .chain(portal)
             .collect()
     }
 }
@@ -155,30 +196,107 @@ impl<'a> Graph for RecursiveMaze<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
+    use crate::pathfinding::{CountingGraph, StopReason};
+
+    fn read_data(file: &str) -> Maze {
+        parse(&input::read(file))
+    }
 
     #[test]
     fn example1() {
-        let maze = read_data("data/day20-example1.txt");
-        assert_eq!(maze.route().len(), 23);
+        let maze = read_data("day20-example1.txt");
+        assert_eq!(maze.distance(), 23);
 
         let rec_maze = RecursiveMaze::new(&maze);
-        assert_eq!(rec_maze.route().len(), 26);
+        assert_eq!(rec_maze.distance(), 26);
     }
 
     #[test]
     fn example2() {
-        let maze = read_data("data/day20-example2.txt");
-        assert_eq!(maze.route().len(), 58);
+        let maze = read_data("day20-example2.txt");
+        assert_eq!(maze.distance(), 58);
 
-        // No recursive route
+        // No recursive route, the search has to be cut off
+        let rec_maze = RecursiveMaze::new(&maze);
+        let route = rec_maze.search(SearchLimits::default().max_expansions(10000));
+        assert_eq!((route.goal(), route.stop_reason()), (None, StopReason::MaxExpansions));
+        let route = rec_maze.search(SearchLimits::default().max_cost(1000));
+        assert_eq!((route.goal(), route.stop_reason()), (None, StopReason::MaxCost));
     }
 
     #[test]
     fn example3() {
-        let maze = read_data("data/day20-example3.txt");
-        assert_eq!(maze.route().len(), 77); // not provided by example
+        let maze = read_data("day20-example3.txt");
+        assert_eq!(maze.distance(), 77); // not provided by example
+
+        let rec_maze = RecursiveMaze::new(&maze);
+        assert_eq!(rec_maze.distance(), 396);
+    }
+
+    parameterized_test::create!{ strategies, file, {
+        let maze = read_data(file);
+        let expected = maze.dijkstras(&maze.start, |n| n == &maze.goal).goal_cost();
+        assert_eq!(maze.bfs(&maze.start, |n| n == &maze.goal).goal_cost(), expected);
+        assert_eq!(maze.bfs_01(&maze.start, |n| n == &maze.goal).goal_cost(), expected);
+        assert_eq!(maze.bidirectional_bfs(&maze.start, &maze.goal).goal_cost(), expected);
+        assert_eq!(maze.bidirectional_dijkstras(&maze.start, &maze.goal).goal_cost(), expected);
+        assert_eq!(maze.parallel_bfs(&maze.start, |n| n == &maze.goal, 4).goal_cost(), expected);
+        assert_eq!(maze.parallel_dijkstras(&maze.start, |n| n == &maze.goal, 3, 4).goal_cost(), expected);
+    }}
+    strategies! {
+        example1: "day20-example1.txt",
+        example2: "day20-example2.txt",
+        example3: "day20-example3.txt",
+    }
+
+    // The maze with free portals, so searches see a mix of zero- and one-weight edges
+    struct FreePortals<'a>(&'a Maze);
+
+    impl<'a> Graph for FreePortals<'a> {
+        type Node = Point;
+        type Weight = i32;
 
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            let portal = self.0.portals.get(source).map(|p| Edge::new(0, *source, *p));
+            Halls(self.0).neighbors(source).into_iter().chain(portal).collect()
+        }
+    }
+
+    impl<'a> ReversibleGraph for FreePortals<'a> {
+        fn predecessors(&self, dest: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            self.neighbors(dest).iter().map(Edge::reversed).collect()
+        }
+    }
+
+    parameterized_test::create!{ zero_weight_strategies, (file, expected), {
+        let maze = read_data(file);
+        let free = FreePortals(&maze);
+        assert_eq!(free.dijkstras(&maze.start, |n| n == &maze.goal).goal_cost(), Some(expected));
+        assert_eq!(free.bfs_01(&maze.start, |n| n == &maze.goal).goal_cost(), Some(expected));
+        assert_eq!(free.bidirectional_dijkstras(&maze.start, &maze.goal).goal_cost(), Some(expected));
+        assert_eq!(free.parallel_dijkstras(&maze.start, |n| n == &maze.goal, 3, 4).goal_cost(), Some(expected));
+        // The routes take portals, so they are shorter when the portals are free
+        assert!(expected < maze.distance());
+    }}
+    zero_weight_strategies! {
+        example1: ("day20-example1.txt", 20),
+        example2: ("day20-example2.txt", 54),
+        example3: ("day20-example3.txt", 72),
+    }
+
+    #[test]
+    fn astar_expands_fewer_nodes() {
+        let maze = read_data("day20-example3.txt");
         let rec_maze = RecursiveMaze::new(&maze);
-        assert_eq!(rec_maze.route().len(), 396);
+        let counter = CountingGraph::new(&rec_maze);
+        let (start, goal) = (PointN::from((maze.start, 0)), PointN::from((maze.goal, 0)));
+
+        let djk_len = counter.dijkstras(&start, |n| n == &goal).goal_cost();
+        let djk_expanded = counter.take();
+        let astar_len = counter.astar(&start, |n| n == &goal, RecursiveMaze::heuristic).goal_cost();
+        let astar_expanded = counter.take();
+        assert_eq!(astar_len, djk_len);
+        assert!(astar_expanded < djk_expanded, "A* expanded {} nodes, Dijkstra's {}", astar_expanded, djk_expanded);
     }
 }
\ No newline at end of file
@@ -1,32 +1,35 @@
+use crate::console;
 use crate::intcode::{Machine, State};
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let image = read_data();
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(21, Machine::from_program, vec!(walk_damage, run_damage))
+}
+
+const WALK_PROGRAM: [&str; 6] = [
+    "NOT A T", // T if A is a hole
+    "NOT B J", // J if B is a hole
+    "OR J T",  // T if A or B is a hole
+    "NOT C J", // J if C is a hole
+    "OR T J", // J if A, B, or C is a hole
+    "AND D J", // J if D is not a hole, and A, B, or C are
+];
 
-    let walk_program = vec!(
-        "NOT A T", // T if A is a hole
-        "NOT B J", // J if B is a hole
-        "OR J T",  // T if A or B is a hole
-        "NOT C J", // J if C is a hole
-        "OR T J", // J if A, B, or C is a hole
-        "AND D J", // J if D is not a hole, and A, B, or C are
-    );
-    let walk_damage = spring(&image, &[&walk_program[..], &["WALK"]].concat()).expect("WALK failed");
-    println!("WALK hull damage: {}", walk_damage);
+fn walk_damage(image: &Machine) -> Answer {
+    let walk_damage = spring(image, &[&WALK_PROGRAM[..], &["WALK"]].concat()).expect("WALK failed");
+    Answer::new("WALK Hull Damage", walk_damage)
+}
 
-    let run_program = vec!(
+fn run_damage(image: &Machine) -> Answer {
+    let run_program = [
         // if and only-if E and H are holes, don't jump
         "NOT H T", // T if H(8) is a hole
         "NOT T T", // T if H(8) is not a hole
         "OR E T", // T if H(8) is not a hole or E(5) is not a hole
-        "AND T J", // J if D is jumpable (per walk_program) and doesn't trap us
-    );
-    let run_damage = spring(&image, &[&walk_program[..], &run_program[..], &["RUN"]].concat()).expect("RUN failed");
-    println!("RUN hull damage:  {}", run_damage);
-}
-
-fn read_data() -> Machine {
-    Machine::from_file("data/day21.txt")
+        "AND T J", // J if D is jumpable (per WALK_PROGRAM) and doesn't trap us
+    ];
+    let run_damage = spring(image, &[&WALK_PROGRAM[..], &run_program[..], &["RUN"]].concat()).expect("RUN failed");
+    Answer::new("RUN Hull Damage", run_damage)
 }
 
 fn spring(image: &Machine, program: &[&str]) -> Option<i64> {
@@ -43,11 +46,9 @@ fn spring(image: &Machine, program: &[&str]) -> Option<i64> {
         State::Halt => Some(machine.read_output()[0]),
         State::Output => {
             machine.run();
-            println!("{}", machine.read_output_ascii());
+            console::write(format!("{}\n", machine.read_output_ascii()));
             None
         }
         _ => panic!(),
     }
 }
-
-// TODO (change detector) tests?
\ No newline at end of file
@@ -1,21 +1,26 @@
 use std::str::FromStr;
 use regex::Regex;
 use crate::error::ParseError;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::puzzle::{Answer, Puzzle};
 
 const SMALL_SIZE: i64 = 10007;
 const LARGE_SIZE: i64 = 119315717514047;
 const LARGE_REPEATS: i64 = 101741582076661;
 
-pub fn advent() {
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(22, parse, vec!(position_of_2019, card_at_2020))
+}
+
+fn parse(input: &str) -> Vec<Move> {
+    input.lines().map(|l| l.parse().unwrap()).collect()
+}
+
+fn position_of_2019(moves: &[Move]) -> Answer {
     let mut deck: Vec<_> = (0..SMALL_SIZE as usize).collect();
-    let moves = read_data();
     for m in moves.iter() {
         deck = m.apply(deck);
     }
     let pos_2019 = deck.iter().position(|&v| v == 2019).unwrap();
-    println!("Position of card 2019: {}", pos_2019);
 
     // Applying the same approach isn't feasible for a deck, or even a single index, of the larger size
     // Need to utilize modular arithmetic ¯\_(ツ)_/¯
@@ -28,17 +33,16 @@ pub fn advent() {
     }
     assert_eq!(repr.card_at(pos_2019 as u64), 2019);
 
+    Answer::new("Position of Card 2019", pos_2019)
+}
+
+fn card_at_2020(moves: &[Move]) -> Answer {
     let mut repr = DeckRepr::new(LARGE_SIZE);
     for m in moves.iter() {
         m.apply_repr(&mut repr);
     }
     repr.repeat(LARGE_REPEATS);
-    println!("Card at 2020: {}", repr.card_at(2020));
-}
-
-fn read_data() -> Vec<Move> {
-    let reader = BufReader::new(File::open("data/day22.txt").expect("Cannot open"));
-    reader.lines().map(|l| l.unwrap().parse().unwrap()).collect()
+    Answer::new("Card at 2020", repr.card_at(2020))
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -200,7 +204,7 @@ impl DeckRepr {
     }
 
     #[cfg(test)]
-    fn apply_deck(&self, deck: &Vec<usize>) -> Vec<usize> {
+    fn apply_deck(&self, deck: &[usize]) -> Vec<usize> {
         (0..deck.len()).map(|i| deck[self.card_at(i as u64) as usize]).collect()
     }
 }
@@ -249,7 +253,7 @@ mod tests {
             m.apply_repr(&mut repr);
         }
 
-        let repr_deck = repr.apply_deck(&(0..10).collect());
+        let repr_deck = repr.apply_deck(&(0..10).collect::<Vec<_>>());
         assert_eq!(repr_deck, expected);
         assert_eq!((repr.offset, repr.increment), exp_repr);
     }}
@@ -1,8 +1,22 @@
 use crate::intcode::{Machine, State};
+use crate::puzzle::{Answer, Puzzle};
 use std::collections::{HashMap, VecDeque, HashSet};
 
-pub fn advent() {
-    let image = Machine::from_file("data/day23.txt");
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(23, Machine::from_program, vec!(first_nat_packet, first_repeated_nat_packet))
+}
+
+fn first_nat_packet(image: &Machine) -> Answer {
+    Answer::new("First NAT Packet Y", run_network(image, true))
+}
+
+fn first_repeated_nat_packet(image: &Machine) -> Answer {
+    Answer::new("First Repeated NAT Y", run_network(image, false))
+}
+
+// Runs the network until the NAT receives its first packet, if stop_at_first_nat is set, or until
+// the NAT delivers a Y value it has already delivered before, returning the Y value in question.
+fn run_network(image: &Machine, stop_at_first_nat: bool) -> i64 {
     let mut machines: Vec<_> = (0..50).map(|i| {
         let mut machine = image.clone();
         machine.send_input(i);
@@ -10,7 +24,6 @@ pub fn advent() {
     }).collect();
 
     let mut queues: HashMap<usize, VecDeque<i64>> = (0..50).map(|i| (i, VecDeque::new())).collect();
-    let mut seen_nat = false;
     let mut nat: Option<(i64, i64)> = None;
     let mut seen_idle_ys = HashSet::new();
 
@@ -23,9 +36,8 @@ pub fn advent() {
                     let packet = machine.read_output();
                     assert_eq!(packet.len(), 3);
                     if packet[0] == 255 {
-                        if !seen_nat {
-                            println!("First NAT Packet Y:   {}", &packet[2]);
-                            seen_nat = true;
+                        if stop_at_first_nat {
+                            return packet[2];
                         }
                         nat = Some((packet[1], packet[2]));
 
@@ -55,13 +67,11 @@ pub fn advent() {
         if idle {
             if let Some((x, y))= nat.take() {
                 if !seen_idle_ys.insert(y) {
-                    println!("First repeated NAT Y: {}", y);
-                    break;
+                    return y;
                 }
                 machines[0].send_input(x);
                 machines[0].send_input(y);
             }
         }
     }
-
 }
\ No newline at end of file
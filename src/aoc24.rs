@@ -1,46 +1,45 @@
-use std::collections::{HashSet, VecDeque};
-use crate::euclid::{point, Point, vector};
+use std::collections::HashSet;
+use crate::euclid::{point, Direction4, Grid, Point};
+use crate::euclid3d::{point as point3, Grid3, Point3, vector as vector3};
 use crate::error::ParseError;
 use std::str::FromStr;
+use crate::puzzle::{Answer, Puzzle};
 
-const INPUT: &str = "###.#\n\
-                     ..#..\n\
-                     #..#.\n\
-                     #....\n\
-                     .#.#.";
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(24, parse, vec!(bio_rating, recursive_bug_count))
+}
+
+fn parse(input: &str) -> Biosphere {
+    input.parse().expect("Invalid")
+}
 
-pub fn advent() {
-    let mut bio: Biosphere = INPUT.parse().unwrap();
+fn bio_rating(bio: &Biosphere) -> Answer {
+    let mut bio = bio.clone();
     bio.step_until();
-    println!("Single-layer Bio Rating: {}", bio.rating());
+    Answer::new("Single-layer Bio Rating", bio.rating())
+}
 
-    let mut rec_bio = RecBiosphere::new(&INPUT.parse().unwrap());
+fn recursive_bug_count(bio: &Biosphere) -> Answer {
+    let mut rec_bio = RecBiosphere::new(bio);
     for _ in 0..200 {
         rec_bio.step();
     }
-    println!("Recursive bug count after 200 minutes: {}", rec_bio.count());
+    Answer::new("Recursive Bug Count After 200 Minutes", rec_bio.count())
 }
 
+#[derive(Clone)]
 struct Biosphere {
-    bugs: HashSet<Point>,
+    bugs: Grid<bool>,
 }
 
 impl Biosphere {
     fn step(&mut self) {
-        let past_iter: HashSet<_> = self.bugs.drain().collect();
-        for y in 0..5 {
-            for x in 0..5 {
-                let coord = point(x, y);
-                let neighbors = Biosphere::neighbors(&past_iter, &coord);
-                let live = match past_iter.get(&coord) {
-                    Some(_) => neighbors == 1,
-                    None => neighbors == 1 || neighbors == 2,
-                };
-                if live {
-                    self.bugs.insert(coord);
-                }
-            }
-        }
+        let past_iter = &self.bugs;
+        let bugs = Grid::from_fn(Point::ORIGIN, past_iter.width(), past_iter.height(), |coord| {
+            let neighbors = past_iter.neighbors4(coord).filter(|&n| past_iter[n]).count();
+            if past_iter[coord] { neighbors == 1 } else { neighbors == 1 || neighbors == 2 }
+        });
+        self.bugs = bugs;
     }
 
     fn step_until(&mut self) {
@@ -50,14 +49,8 @@ impl Biosphere {
         }
     }
 
-    fn neighbors(points: &HashSet<Point>, point: &Point) -> u32 {
-        [vector(-1, 0), vector(1, 0), vector(0, -1), vector(0, 1)].iter()
-            .filter(|&v| { let p = *point+v; points.contains(&p) })
-            .count() as u32
-    }
-
     fn rating(&self) -> u64 {
-        self.bugs.iter().map(|p| 2_u64.pow((p.y*5+p.x) as u32)).sum()
+        self.bugs.iter().filter(|(_, &bug)| bug).map(|(p, _)| 2_u64.pow((p.y*5+p.x) as u32)).sum()
     }
 }
 
@@ -65,98 +58,61 @@ impl FromStr for Biosphere {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, ParseError> {
-        let mut bugs = HashSet::new();
-
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let coord = point(x as i32, y as i32);
-                match c {
-                    '#' => { bugs.insert(coord); },
-                    '.'|'?' => {},
-                    _ => panic!(),
-                }
-            }
-        }
-
+        let bugs = Grid::parse(s, |c| match c {
+            '#' => Some(true),
+            '.'|'?' => Some(false),
+            _ => None,
+        })?;
         Ok(Biosphere { bugs })
     }
 }
 
+// Each layer is one z coordinate; the layer inside a layer's center tile is at z+1
 struct RecBiosphere {
-    layers: VecDeque<HashSet<Point>>,
+    bugs: Grid3<bool>,
 }
 
 impl RecBiosphere {
     fn new(biosphere: &Biosphere) -> RecBiosphere {
-        let layer = biosphere.bugs.clone();
-        let layers: VecDeque<_> = vec!(HashSet::new(), layer, HashSet::new()).into_iter().collect();
-        RecBiosphere { layers }
+        RecBiosphere { bugs: Grid3::from_layers(std::slice::from_ref(&biosphere.bugs)) }
     }
 
     fn step(&mut self) {
-        let past_iter: Vec<_> = self.layers.drain(..).collect();
-        self.layers.push_back(HashSet::new());
-        for (i, past_layer) in past_iter.iter().enumerate() {
-            let mut layer = HashSet::new();
-            for y in 0..5 {
-                for x in 0..5 {
-                    let coord = point(x, y);
-                    if coord == point(2,2) { continue; }
-                    let neighbors = RecBiosphere::neighbors(&past_iter, i, &coord);
-                    let live = match past_layer.get(&coord) {
-                        Some(_) => neighbors == 1,
-                        None => neighbors == 1 || neighbors == 2,
-                    };
-                    if live {
-                        layer.insert(coord);
-                    }
-                }
-            }
-            self.layers.push_back(layer);
-        }
-        self.layers.push_back(HashSet::new());
+        let past_iter = &self.bugs;
+        let (min, _) = past_iter.bounds();
+        // Bugs can spread one layer further in each direction every minute
+        let bugs = Grid3::from_fn(min + vector3(0, 0, -1), past_iter.width(), past_iter.height(), past_iter.depth() + 2, |coord| {
+            if (coord.x, coord.y) == (2, 2) { return false; }
+            let neighbors = RecBiosphere::neighbors(past_iter, coord);
+            if past_iter.get(coord) == Some(&true) { neighbors == 1 } else { neighbors == 1 || neighbors == 2 }
+        });
+        self.bugs = bugs;
     }
 
-    fn neighbors(layers: &[HashSet<Point>], layer: usize, p: &Point) -> u32 {
-        let p = *p;
-        let same = [vector(-1, 0), vector(1, 0), vector(0, -1), vector(0, 1)].iter()
-            .filter(|&v| { let n = p+v; layers[layer].contains(&n) })
-            .count() as u32;
-
-        // TODO these nested and outer calculations feel inelegant...
-
-        let nested =
-            if layer < layers.len()-1 {
-                if p == point(1, 2) {
-                    layers[layer + 1].iter().filter(|p| p.x == 0).count()
-                } else if p == point(2, 1) {
-                    layers[layer + 1].iter().filter(|p| p.y == 0).count()
-                } else if p == point(3, 2) {
-                    layers[layer + 1].iter().filter(|p| p.x == 4).count()
-                } else if p == point(2, 3) {
-                    layers[layer + 1].iter().filter(|p| p.y == 4).count()
-                } else { 0 }
-            } else { 0 };
-
-        let outer =
-        if layer > 0 {
-            (if p.x == 0 {
-                if layers[layer - 1].contains(&point(1, 2)) { 1 } else { 0 }
-            } else if p.x == 4 {
-                if layers[layer - 1].contains(&point(3, 2)) { 1 } else { 0 }
-            } else { 0 }) +
-                (if p.y == 0 {
-                if layers[layer - 1].contains(&point(2, 1)) { 1 } else { 0 }
-            } else if p.y == 4 {
-                if layers[layer - 1].contains(&point(2, 3)) { 1 } else { 0 }
-            } else { 0 })
-        } else { 0 };
-
-        same + nested as u32 + outer
+    fn neighbors(bugs: &Grid3<bool>, p: Point3<i32>) -> usize {
+        let bug = |p| bugs.get(p) == Some(&true);
+        Direction4::ALL.iter().map(|d| {
+            let v = d.vector();
+            let n = point(p.x, p.y) + v;
+            if n == point(2, 2) {
+                // The edge of the nested layer facing p
+                (0..5).filter(|&i| bug(match d {
+                    Direction4::East => point3(0, i, p.z + 1),
+                    Direction4::West => point3(4, i, p.z + 1),
+                    Direction4::South => point3(i, 0, p.z + 1),
+                    Direction4::North => point3(i, 4, p.z + 1),
+                })).count()
+            } else if !n.in_bounds(point(0, 0), point(4, 4)) {
+                // The tile adjacent to the center of the outer layer
+                bug(point3(2 + v.x, 2 + v.y, p.z - 1)) as usize
+            } else {
+                bug(point3(n.x, n.y, p.z)) as usize
+            }
+        }).sum()
     }
 
     fn count(&self) -> usize {
-        self.layers.iter().map(|l| l.len()).sum()
+        self.bugs.iter().filter(|(_, &bug)| bug).count()
     }
 }
 
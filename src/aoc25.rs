@@ -1,24 +1,26 @@
 use crate::intcode::{Machine, State};
-use std::io::{stdin, stdout, Write};
+use crate::puzzle::{Answer, Puzzle};
+use crate::console;
+use std::io::stdin;
 
-pub fn advent() {
-    let image = read_data();
-    if interactive!() {
-        let mut input = String::new();
-        print!("Explore manually? [y/N]: ");
-        stdout().flush().unwrap();
-        stdin().read_line(&mut input).expect("Failed");
-        if input.trim().to_ascii_lowercase() == "y" {
-            interactive(&image);
-            return;
-        }
+pub fn puzzle() -> Puzzle {
+    Puzzle::interactive(25, Machine::from_program, vec!(santas_greeting), explore)
+}
+
+// Offers to explore the ship by hand, in which case there's nothing left to solve
+fn explore(image: &Machine) -> bool {
+    let mut input = String::new();
+    console::write("Explore manually? [y/N]: ");
+    stdin().read_line(&mut input).expect("Failed");
+    if input.trim().eq_ignore_ascii_case("y") {
+        interactive(image);
+        return true;
     }
-    let santas_greeting = pre_explored(&image);
-    println!("Santa says: {}", santas_greeting);
+    false
 }
 
-fn read_data() -> Machine {
-    Machine::from_file("data/day25.txt")
+fn santas_greeting(image: &Machine) -> Answer {
+    Answer::new("Santa Says", pre_explored(image))
 }
 
 fn pre_explored(image: &Machine) -> String {
@@ -77,7 +79,7 @@ fn interactive(image: &Machine) {
     let mut machine = image.clone();
     loop {
         let state = machine.run();
-        println!("\n{}", machine.read_output_ascii());
+        console::write(format!("\n{}\n", machine.read_output_ascii()));
 
         match state {
             State::Halt => { break; }
@@ -1,15 +1,26 @@
-const PUZZLE_INPUT: (u32, u32) = (172930, 683082);
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let (first, second) = find_passwords();
-    println!("First Hints: {}", first);
-    println!("Second Hint: {}", second);
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(4, parse, vec!(first_hint, second_hint))
 }
 
-fn find_passwords() -> (u32, u32) {
+fn parse(input: &str) -> (u32, u32) {
+    let (low, high) = input.trim().split_once('-').expect("Invalid range");
+    (low.parse().expect("Invalid"), high.parse().expect("Invalid"))
+}
+
+fn first_hint(range: &(u32, u32)) -> Answer {
+    Answer::new("First Hints", find_passwords(*range).0)
+}
+
+fn second_hint(range: &(u32, u32)) -> Answer {
+    Answer::new("Second Hint", find_passwords(*range).1)
+}
+
+fn find_passwords(range: (u32, u32)) -> (u32, u32) {
     let mut first = 0;
     let mut second = 0;
-    for n in PUZZLE_INPUT.0..PUZZLE_INPUT.1 {
+    for n in range.0..range.1 {
         if never_decrease(n) && two_adjacent(n) {
             first+=1;
             if exactly_two_adjacent(n) {
@@ -62,10 +73,11 @@ fn exactly_two_adjacent(n: u32) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
     #[test]
     fn puzzle_input() {
-        assert_eq!(find_passwords(), (1675, 1142));
+        assert_eq!(find_passwords(parse(&input::read("day4.txt"))), (1675, 1142));
     }
 
     parameterized_test::create! { facts, (n, nd, ta, eta), {
@@ -1,20 +1,23 @@
 use crate::intcode::Machine;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let mut machine = read_data();
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(5, Machine::from_program, vec!(ac_diagnostic, radiator_diagnostic))
+}
+
+fn ac_diagnostic(image: &Machine) -> Answer {
+    let mut machine = image.clone();
     machine.send_input(1);
     machine.run();
     let diagnostic = machine.read_output();
-    println!("AC Diagnostic: {}", diagnostic[diagnostic.len() - 1]);
+    Answer::new("AC Diagnostic", diagnostic[diagnostic.len() - 1])
+}
 
-    let mut machine = read_data();
+fn radiator_diagnostic(image: &Machine) -> Answer {
+    let mut machine = image.clone();
     machine.send_input(5);
     machine.run();
-    println!("Radiator Diagnostic: {}", machine.read_output()[0]);
-}
-
-fn read_data() -> Machine {
-    Machine::from_file("data/day5.txt")
+    Answer::new("Radiator Diagnostic", machine.read_output()[0])
 }
 
 #[cfg(test)]
@@ -23,6 +26,6 @@ mod tests {
 
     #[test]
     fn read_file() {
-        read_data();
+        Machine::from_input("day5.txt");
     }
 }
@@ -1,24 +1,19 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use regex::Regex;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let orbit_map = read_data();
-    println!("Checksum: {}", orbit_checksum(&orbit_map));
-    println!("Min Transfers: {}", orbital_transfers(&orbit_map, "YOU", "SAN"));
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(6, parse, vec!(checksum, min_transfers))
 }
 
-fn read_data() -> HashMap<String, String> {
+fn parse(input: &str) -> HashMap<String, String> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"^(\w+)\)(\w+)$").unwrap();
     }
 
-    let lines = BufReader::new(File::open("data/day6.txt").expect("Cannot open")).lines();
     let mut orbit_map = HashMap::new();
-    for orbit in lines {
-        let orbit = orbit.unwrap();
-        let caps = regex_captures!(RE, &orbit).expect("Invalid input");
+    for orbit in input.lines() {
+        let caps = regex_captures!(RE, orbit).expect("Invalid input");
         let parent = capture_group!(caps, 1);
         let satellite = capture_group!(caps, 2);
         assert!(!orbit_map.contains_key(satellite));
@@ -27,11 +22,18 @@ fn read_data() -> HashMap<String, String> {
     orbit_map
 }
 
-fn orbit_checksum(orbit_map: &HashMap<String, String>) -> u32 {
+fn checksum(orbit_map: &HashMap<String, String>) -> Answer {
     let mut depth_cache = HashMap::new();
-    let ret = orbit_map.keys().map(|body| orbit_depth(orbit_map, body, &mut depth_cache)).sum();
-    println!("Cache size: {}", depth_cache.len());
-    ret
+    let checksum = orbit_checksum(orbit_map, &mut depth_cache);
+    Answer::new("Checksum", checksum).with_detail("Cache Size", depth_cache.len())
+}
+
+fn min_transfers(orbit_map: &HashMap<String, String>) -> Answer {
+    Answer::new("Min Transfers", orbital_transfers(orbit_map, "YOU", "SAN"))
+}
+
+fn orbit_checksum(orbit_map: &HashMap<String, String>, depth_cache: &mut HashMap<String, u32>) -> u32 {
+    orbit_map.keys().map(|body| orbit_depth(orbit_map, body, depth_cache)).sum()
 }
 
 fn orbit_depth(orbit_map: &HashMap<String, String>, body: &str, depth_cache: &mut HashMap<String, u32>) -> u32 {
@@ -78,6 +80,7 @@ fn orbital_transfers(orbit_map: &HashMap<String, String>, source: &str, dest: &s
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
     #[test]
     fn checksum() {
@@ -94,7 +97,7 @@ mod tests {
             ("K", "J"),
             ("L", "K"),
         ].iter().cloned().map(|(k, v)| (k.into(), v.into())).collect();
-        assert_eq!(orbit_checksum(&orbit_map), 42);
+        assert_eq!(orbit_checksum(&orbit_map, &mut HashMap::new()), 42);
     }
 
     #[test]
@@ -119,6 +122,6 @@ mod tests {
 
     #[test]
     fn read_file() {
-        assert!(read_data().len() > 0)
+        assert!(!parse(&input::read("day6.txt")).is_empty())
     }
 }
\ No newline at end of file
@@ -1,16 +1,19 @@
 use crate::intcode::{Machine, State};
 use permutohedron::LexicalPermutation;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let image = read_data();
-    let max = find_maximum_signal(&image);
-    println!("         Sequence: {:?} - Signal: {:8}", max.0, max.1);
-    let max = find_maximum_feedback_signal(&image);
-    println!("Feedback Sequence: {:?} - Signal: {:8}", max.0, max.1);
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(7, Machine::from_program, vec!(max_signal, max_feedback_signal))
 }
 
-fn read_data() -> Machine {
-    Machine::from_file("data/day7.txt")
+fn max_signal(image: &Machine) -> Answer {
+    let max = find_maximum_signal(image);
+    Answer::new("Max Signal", max.1).with_detail("Sequence", format!("{:?}", max.0))
+}
+
+fn max_feedback_signal(image: &Machine) -> Answer {
+    let max = find_maximum_feedback_signal(image);
+    Answer::new("Max Feedback Signal", max.1).with_detail("Sequence", format!("{:?}", max.0))
 }
 
 fn compute_signal(image: &Machine, sequence: &[i64]) -> i64 {
@@ -104,6 +107,6 @@ mod tests {
 
     #[test]
     fn read_file() {
-        read_data();
+        Machine::from_input("day7.txt");
     }
 }
@@ -1,18 +1,22 @@
-use std::fs;
+use crate::puzzle::{Answer, Puzzle};
+use crate::console::Renderer;
 
 const IMAGE_DIM: (usize, usize) = (25, 6);
 
-pub fn advent() {
-    let layers = read_data();
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(8, parse, vec!(image_checksum, password))
+}
 
-    println!("Checksum: {}", checksum(&layers));
+fn parse(input: &str) -> Vec<String> {
+    partition(input.trim(), IMAGE_DIM.0*IMAGE_DIM.1)
+}
 
-    println!("Password:");
-    println!("{}", render_image(&decode_image(&layers, IMAGE_DIM.0, IMAGE_DIM.1), 25));
+fn image_checksum(layers: &[String]) -> Answer {
+    Answer::new("Checksum", checksum(layers))
 }
 
-fn read_data() -> Vec<String> {
-    partition(fs::read_to_string("data/day8.txt").expect("Cannot open").trim(), IMAGE_DIM.0*IMAGE_DIM.1)
+fn password(layers: &[String]) -> Answer {
+    Answer::new("Password", render_image(&decode_image(layers, IMAGE_DIM.0, IMAGE_DIM.1), IMAGE_DIM.0))
 }
 
 fn partition(input: &str, size: usize) -> Vec<String> {
@@ -43,14 +47,15 @@ fn checksum(layers: &[String]) -> usize {
 fn decode_image(layers: &[String], width: usize, height: usize) -> String {
     // This could be a fold, e.g. `.fold("2".repeat(25*6), merge_layer)`, but it's done as a loop
     // here so the intermediate stages can be printed
+    let mut renderer = Renderer::new().frame_rate(40).record("day8-layers");
     let mut image = "2".repeat(width*height);
     for layer in layers {
-        if cfg!(debug_assertions) {
-            println!("{}\u{001B}[{}A", render_image(&image, width), height);
-            std::thread::sleep(std::time::Duration::from_millis(25));
+        if renderer.enabled() {
+            renderer.draw(render_image(&image, width));
         }
         image = merge_layer(image, layer);
     }
+    renderer.clear();
     image
 }
 
@@ -77,10 +82,11 @@ fn render_image(image: &str, width: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input;
 
     #[test]
     fn read_file() {
-        assert!(!read_data().is_empty());
+        assert!(!parse(&input::read("day8.txt")).is_empty());
     }
 
     #[test]
@@ -1,19 +1,22 @@
 use crate::intcode::Machine;
+use crate::puzzle::{Answer, Puzzle};
 
-pub fn advent() {
-    let mut machine: Machine = read_data();
+pub fn puzzle() -> Puzzle {
+    Puzzle::new(9, Machine::from_program, vec!(keycode, coordinates))
+}
+
+fn keycode(image: &Machine) -> Answer {
+    let mut machine = image.clone();
     machine.send_input(1);
     machine.run();
-    println!("Keycode: {}", machine.read_output()[0]);
+    Answer::new("Keycode", machine.read_output()[0])
+}
 
-    let mut machine: Machine = read_data();
+fn coordinates(image: &Machine) -> Answer {
+    let mut machine = image.clone();
     machine.send_input(2);
     machine.run();
-    println!("Coordinates: {}", machine.read_output()[0]);
-}
-
-fn read_data() -> Machine {
-    Machine::from_file("data/day9.txt")
+    Answer::new("Coordinates", machine.read_output()[0])
 }
 
 #[cfg(test)]
@@ -22,6 +25,6 @@ mod tests {
 
     #[test]
     fn read_file() {
-        read_data();
+        Machine::from_input("day9.txt");
     }
 }
\ No newline at end of file
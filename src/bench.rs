@@ -0,0 +1,216 @@
+// Times parsing and each part of the selected days over several iterations, optionally comparing
+// the median timings against a previously saved baseline to flag regressions.
+//
+// Best run in release mode, e.g.:
+// $ cargo run --release -- bench --iterations 20 --save /tmp/baseline.txt 1 2 3
+// $ cargo run --release -- bench --iterations 20 --baseline /tmp/baseline.txt 1 2 3
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::time::Duration;
+use crate::console;
+use crate::input;
+use crate::puzzle::Puzzle;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Stage {
+    Parse,
+    Part(usize),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Parse => fmt::Display::fmt("parse", f),
+            Stage::Part(n) => fmt::Display::fmt(&format!("part{}", n), f),
+        }
+    }
+}
+
+impl std::str::FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s == "parse" { return Ok(Stage::Parse); }
+        s.strip_prefix("part").and_then(|n| n.parse().ok()).map(Stage::Part)
+            .ok_or_else(|| format!("Invalid stage: {}", s))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    fn of(samples: &mut [Duration]) -> Stats {
+        assert!(!samples.is_empty());
+        samples.sort();
+        let mid = samples.len() / 2;
+        let median = if samples.len() % 2 == 1 { samples[mid] } else { (samples[mid - 1] + samples[mid]) / 2 };
+        Stats { min: samples[0], median, max: samples[samples.len() - 1] }
+    }
+}
+
+pub type Timings = BTreeMap<(u32, Stage), Stats>;
+
+pub fn benchmark(puzzle: &Puzzle, iterations: usize) -> Timings {
+    assert!(iterations > 0);
+    // Read the input once up front so that I/O isn't included in the parse timings
+    let input = input::read(&puzzle.input_name());
+    let mut samples: BTreeMap<Stage, Vec<Duration>> = BTreeMap::new();
+    for _ in 0..iterations {
+        let solution = puzzle.solve(&input);
+        samples.entry(Stage::Parse).or_default().push(solution.parse);
+        for (i, (_, elapsed)) in solution.parts.iter().enumerate() {
+            samples.entry(Stage::Part(i + 1)).or_default().push(*elapsed);
+        }
+    }
+    samples.into_iter().map(|(stage, mut s)| ((puzzle.day(), stage), Stats::of(&mut s))).collect()
+}
+
+// Baselines are stored as whitespace-separated "DAY STAGE MEDIAN_NANOS" lines
+pub fn format_baseline(timings: &Timings) -> String {
+    timings.iter()
+        .map(|((day, stage), stats)| format!("{} {} {}\n", day, stage, stats.median.as_nanos()))
+        .collect()
+}
+
+pub fn parse_baseline(baseline: &str) -> Result<BTreeMap<(u32, Stage), Duration>, String> {
+    baseline.lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .map(|l| {
+            let parts: Vec<_> = l.split_whitespace().collect();
+            if parts.len() != 3 { return Err(format!("Invalid baseline line: {}", l)); }
+            let day = parts[0].parse().map_err(|e| format!("Invalid day in {}: {}", l, e))?;
+            let stage = parts[1].parse()?;
+            let nanos = parts[2].parse().map_err(|e| format!("Invalid time in {}: {}", l, e))?;
+            Ok(((day, stage), Duration::from_nanos(nanos)))
+        })
+        .collect()
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Regression {
+    pub day: u32,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub median: Duration,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let change = self.median.as_secs_f64() / self.baseline.as_secs_f64() * 100.0 - 100.0;
+        write!(f, "Day {} {}: median {:.2?} vs. baseline {:.2?} ({:+.1}%)",
+               self.day, self.stage, self.median, self.baseline, change)
+    }
+}
+
+// Flags any stage whose median is more than threshold (e.g. 0.1 for 10%) slower than its baseline
+pub fn regressions(timings: &Timings, baseline: &BTreeMap<(u32, Stage), Duration>, threshold: f64) -> Vec<Regression> {
+    timings.iter()
+        .filter_map(|(key, stats)| {
+            let base = *baseline.get(key)?;
+            if stats.median.as_secs_f64() > base.as_secs_f64() * (1.0 + threshold) {
+                Some(Regression { day: key.0, stage: key.1, baseline: base, median: stats.median })
+            } else { None }
+        })
+        .collect()
+}
+
+fn format_timings(timings: &Timings) -> String {
+    let mut out = format!("{:>3} {:<6} {:>12} {:>12} {:>12}\n", "Day", "Stage", "Min", "Median", "Max");
+    for ((day, stage), stats) in timings {
+        out.push_str(&format!("{:>3} {:<6} {:>12.2?} {:>12.2?} {:>12.2?}\n",
+                              day, stage.to_string(), stats.min, stats.median, stats.max));
+    }
+    out
+}
+
+pub fn main(puzzles: &[Puzzle], args: &[String]) -> Result<(), String> {
+    // Rendering would only slow the benchmarks down
+    console::set_output(console::Output::Off);
+    let mut iterations = 10;
+    let mut baseline = None;
+    let mut save = None;
+    let mut threshold = 0.1;
+    let mut days = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} requires a value", arg));
+        match arg.as_str() {
+            "--iterations" => iterations = value()?.parse().map_err(|e| format!("Invalid iterations: {}", e))?,
+            "--baseline" => baseline = Some(value()?.clone()),
+            "--save" => save = Some(value()?.clone()),
+            "--threshold" => threshold = value()?.parse::<f64>().map_err(|e| format!("Invalid threshold: {}", e))? / 100.0,
+            day => days.push(day.parse::<u32>().map_err(|_| format!("Unexpected argument: {}", day))?),
+        }
+    }
+    if iterations == 0 { return Err("Must run at least one iteration".into()); }
+
+    let mut timings = Timings::new();
+    for puzzle in puzzles.iter().filter(|p| days.is_empty() || days.contains(&p.day())) {
+        timings.extend(benchmark(puzzle, iterations));
+    }
+    print!("{}", format_timings(&timings));
+
+    if let Some(save) = save {
+        fs::write(&save, format_baseline(&timings)).map_err(|e| format!("Cannot write {}: {}", save, e))?;
+    }
+
+    if let Some(baseline) = baseline {
+        let baseline = fs::read_to_string(&baseline).map_err(|e| format!("Cannot open {}: {}", baseline, e))?;
+        let regressions = regressions(&timings, &parse_baseline(&baseline)?, threshold);
+        if !regressions.is_empty() {
+            let regressions: Vec<_> = regressions.iter().map(|r| r.to_string()).collect();
+            return Err(format!("Regressions found:\n{}", regressions.join("\n")));
+        }
+        println!("No regressions over {}%", threshold * 100.0);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration { Duration::from_millis(millis) }
+
+    parameterized_test::create!{ stats, (samples, expected), {
+        let mut samples: Vec<_> = samples.iter().map(|&s| ms(s)).collect();
+        let (min, median, max) = expected;
+        assert_eq!(Stats::of(&mut samples), Stats { min: ms(min), median: ms(median), max: ms(max) });
+    }}
+    stats! {
+        one: ([5], (5, 5, 5)),
+        odd: ([9, 1, 5], (1, 5, 9)),
+        even: ([8, 2, 4, 6], (2, 5, 8)),
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let stats = |m| Stats { min: ms(1), median: ms(m), max: ms(100) };
+        let timings: Timings = [((1, Stage::Parse), stats(2)), ((1, Stage::Part(1)), stats(20)),
+            ((18, Stage::Part(2)), stats(50))].iter().cloned().collect();
+        let baseline = parse_baseline(&format_baseline(&timings)).unwrap();
+        assert_eq!(baseline.len(), 3);
+        assert_eq!(baseline[&(1, Stage::Part(1))], ms(20));
+
+        assert!(parse_baseline("1 part1").is_err());
+        assert!(parse_baseline("1 phase 10").is_err());
+    }
+
+    #[test]
+    fn flag_regressions() {
+        let stats = |m| Stats { min: ms(m), median: ms(m), max: ms(m) };
+        let timings: Timings = [((1, Stage::Parse), stats(10)), ((1, Stage::Part(1)), stats(15)),
+            ((2, Stage::Part(1)), stats(100))].iter().cloned().collect();
+        let baseline = parse_baseline("1 parse 10000000\n1 part1 10000000\n").unwrap();
+        assert_eq!(regressions(&timings, &baseline, 0.1),
+                   vec!(Regression { day: 1, stage: Stage::Part(1), baseline: ms(10), median: ms(15) }));
+        assert!(regressions(&timings, &baseline, 0.6).is_empty());
+    }
+}
@@ -1,21 +1,307 @@
-pub struct Console;
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::recorder::Recorder;
+
+// Where interactive output (animations, prompts, and anything else that isn't part of a day's
+// results) is written. interactive!() builds write it to stdout, unless stdout is reserved for
+// structured results (e.g. JSON), in which case it's written to stderr; it's turned off entirely
+// while benchmarking or verifying. This is tracked per-thread so that tests can't affect each other.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Output {
+    Stdout,
+    Stderr,
+    Off,
+}
+
+thread_local! {
+    static OUTPUT: Cell<Output> = const { Cell::new(if interactive!() { Output::Stdout } else { Output::Off }) };
+    // Tests can't inspect what's printed to stdout, so it's collected here instead
+    #[cfg(test)]
+    static CAPTURED: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
+}
+
+pub fn set_output(output: Output) {
+    OUTPUT.with(|o| o.set(output));
+}
+
+pub fn output() -> Output {
+    OUTPUT.with(|o| o.get())
+}
+
+// Whether interactive output is written anywhere; callers can skip constructing it if not
+pub fn interactive() -> bool {
+    output() != Output::Off
+}
+
+fn is_terminal() -> bool {
+    match output() {
+        Output::Stdout => io::stdout().is_terminal(),
+        Output::Stderr => io::stderr().is_terminal(),
+        Output::Off => false,
+    }
+}
+
+// Writes (and flushes, e.g. for prompts) interactive output wherever it's being sent. Like the
+// renderer, this is best-effort; there's nowhere useful to report a failure to write.
+pub fn write(out: impl fmt::Display) {
+    match output() {
+        #[cfg(not(test))]
+        Output::Stdout => {
+            use std::io::Write;
+            let mut stdout = io::stdout().lock();
+            let _ = write!(stdout, "{}", out).and_then(|_| stdout.flush());
+        },
+        #[cfg(test)]
+        Output::Stdout => CAPTURED.with(|c| c.borrow_mut().push_str(&out.to_string())),
+        Output::Stderr => eprint!("{}", out),
+        Output::Off => {},
+    }
+}
+
+// Everything written to stdout by this thread since the last call
+#[cfg(test)]
+pub fn take_stdout() -> String {
+    CAPTURED.with(|c| c.take())
+}
+
+pub struct Console {
+    hidden_cursor: bool,
+}
 
 impl Console {
     #[inline]
     pub fn init() -> Console {
-        if interactive!() {
-            print!("\u{001B}[?25l"); // hide cursor
+        let hidden_cursor = is_terminal();
+        if hidden_cursor {
+            write("\u{001B}[?25l"); // hide cursor
         }
-        Console
+        Console { hidden_cursor }
     }
 }
 
 // Take advantage of Drop to (attempt to) unconditionally restore the cursor. See
 // https://stackoverflow.com/a/57860708/113632 for more, or
 // https://doc.rust-lang.org/std/panic/fn.catch_unwind.html for another potential approach.
-#[cfg(any(feature="interactive", all(debug_assertions, not(test))))]
 impl Drop for Console {
     fn drop(&mut self) {
-        print!("\u{001B}[?25h"); // restore cursor
+        if self.hidden_cursor {
+            write("\u{001B}[?25h"); // restore cursor
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    fn code(&self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+        }
+    }
+}
+
+pub type Palette = fn(char) -> Option<Color>;
+
+// Redraws successive frames (e.g. the state of a game) in place. Frames are double-buffered; only
+// lines that differ from the frame currently on screen are rewritten, and each frame is positioned
+// relative to the previous one's actual line count, rather than a hand-counted number of newlines.
+//
+// Frames are only displayed if interactive() is true, and only recorded if capturing was
+// requested (see recorder.rs); otherwise drawing does nothing, so callers can pass types
+// implementing Display and avoid paying for rendering in tests and release builds. When the
+// output isn't a terminal, e.g. when piped to a file, intermediate frames aren't displayed (or even
+// rendered, unless they're being recorded) and only the final frame passed to finish() is printed.
+pub struct Renderer {
+    display: bool,
+    tty: bool,
+    colors: bool,
+    palette: Option<Palette>,
+    recorder: Option<Recorder>,
+    frame_interval: Option<Duration>,
+    last_draw: Option<Instant>,
+    front: Vec<String>,
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        let tty = is_terminal();
+        Renderer {
+            display: interactive(),
+            tty,
+            colors: tty && std::env::var_os("NO_COLOR").is_none(),
+            palette: None,
+            recorder: None,
+            frame_interval: None,
+            last_draw: None,
+            front: Vec::new(),
+        }
+    }
+
+    // Limits drawing to at most this many frames per second, sleeping between frames as necessary
+    pub fn frame_rate(mut self, fps: u32) -> Renderer {
+        self.frame_interval = Some(Duration::from_secs(1) / fps);
+        self
+    }
+
+    // Colors characters of each frame; not displayed when not writing to a terminal or if
+    // $NO_COLOR is set
+    pub fn palette(mut self, palette: Palette) -> Renderer {
+        self.palette = Some(palette);
+        self
     }
-}
\ No newline at end of file
+
+    // Records the frames under the given name, if capturing was requested
+    pub fn record(mut self, name: &str) -> Renderer {
+        self.recorder = Recorder::for_name(name);
+        self
+    }
+
+    // Whether frames are being displayed or recorded; callers can skip constructing frames if not
+    pub fn enabled(&self) -> bool {
+        self.display || self.recorder.is_some()
+    }
+
+    pub fn draw(&mut self, frame: impl fmt::Display) {
+        let in_place = self.display && self.tty;
+        if !in_place && self.recorder.is_none() { return; }
+        let frame = frame.to_string();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.capture(&frame);
+        }
+        if !in_place { return; }
+
+        if let (Some(interval), Some(last_draw)) = (self.frame_interval, self.last_draw) {
+            if let Some(remaining) = interval.checked_sub(last_draw.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+        write(redraw(&self.front, &frame, if self.colors { self.palette } else { None }));
+        self.front = frame.lines().map(String::from).collect();
+        self.last_draw = Some(Instant::now());
+    }
+
+    // Erases the frame from the screen, for animations that are superseded by later output
+    pub fn clear(self) {
+        if self.display && self.tty && !self.front.is_empty() {
+            write(format!("\u{001B}[{}F\u{001B}[J", self.front.len()));
+        }
+        self.save();
+    }
+
+    // Leaves the last frame on screen, or prints it if frames weren't being drawn in place; callers
+    // pass the last frame they drew, since it's only rendered again if it needs to be printed
+    pub fn finish(self, last_frame: impl fmt::Display) {
+        if self.display && !self.tty {
+            let frame = last_frame.to_string();
+            if !frame.is_empty() {
+                write(format!("{}\n", frame.trim_end_matches('\n')));
+            }
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Some(recorder) = self.recorder.as_ref().filter(|r| !r.is_empty()) {
+            // Recordings are a side-effect of a run; report, but don't fail on, errors saving them
+            match recorder.save(self.palette, self.frame_interval) {
+                Ok(path) => eprintln!("Saved recording to {}", path.display()),
+                Err(e) => eprintln!("Failed to save recording: {}", e),
+            }
+        }
+    }
+}
+
+// Returns the output necessary to replace the lines in front (which the cursor is just below) with
+// those of frame, leaving the cursor just below the new frame
+pub fn redraw(front: &[String], frame: &str, palette: Option<Palette>) -> String {
+    let mut out = String::new();
+    if !front.is_empty() {
+        out.push_str(&format!("\u{001B}[{}F", front.len())); // up to the start of the first line
+    }
+    for (i, line) in frame.lines().enumerate() {
+        if front.get(i).map(|l| l.as_str()) != Some(line) {
+            out.push_str("\u{001B}[2K"); // clear line
+            match palette {
+                Some(palette) => out.push_str(&colorize(line, palette)),
+                None => out.push_str(line),
+            }
+        }
+        out.push('\n');
+    }
+    out.push_str("\u{001B}[J"); // clear anything left below, e.g. if this frame is shorter
+    out
+}
+
+fn colorize(line: &str, palette: Palette) -> String {
+    let mut out = String::new();
+    let mut current = None;
+    for c in line.chars() {
+        let color = palette(c);
+        if color != current {
+            match color {
+                Some(color) => out.push_str(&format!("\u{001B}[{}m", color.code())),
+                None => out.push_str("\u{001B}[0m"),
+            }
+            current = color;
+        }
+        out.push(c);
+    }
+    if current.is_some() {
+        out.push_str("\u{001B}[0m");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(frame: &str) -> Vec<String> { frame.lines().map(String::from).collect() }
+
+    parameterized_test::create!{ redraws, (front, frame, expected), {
+        assert_eq!(redraw(&lines(front), frame, None), expected);
+    }}
+    redraws! {
+        first: ("", "ab\ncd", "\u{1B}[2Kab\n\u{1B}[2Kcd\n\u{1B}[J"),
+        unchanged: ("ab\ncd", "ab\ncd\n", "\u{1B}[2F\n\n\u{1B}[J"),
+        changed: ("ab\ncd", "ab\nce", "\u{1B}[2F\n\u{1B}[2Kce\n\u{1B}[J"),
+        shorter: ("ab\ncd\nef", "ab", "\u{1B}[3F\n\u{1B}[J"),
+        longer: ("ab", "ab\ncd", "\u{1B}[1F\n\u{1B}[2Kcd\n\u{1B}[J"),
+    }
+
+    #[test]
+    fn not_in_place() {
+        set_output(Output::Stdout);
+        let mut renderer = Renderer::new();
+        renderer.tty = false;
+        renderer.draw("ab\ncd");
+        renderer.draw("ab\nce");
+        assert_eq!(take_stdout(), "");
+        assert!(renderer.front.is_empty());
+        renderer.finish("ab\nce\n");
+        assert_eq!(take_stdout(), "ab\nce\n");
+    }
+
+    #[test]
+    fn colors() {
+        let palette: Palette = |c| if c == 'o' { Some(Color::Red) } else if c == '#' { Some(Color::Blue) } else { None };
+        assert_eq!(colorize("#o.oo", palette), "\u{1B}[34m#\u{1B}[31mo\u{1B}[0m.\u{1B}[31moo\u{1B}[0m");
+        assert_eq!(colorize("..", palette), "..");
+    }
+}
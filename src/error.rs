@@ -1,4 +1,5 @@
 use std::error;
+use std::io;
 use std::num;
 use std::fmt;
 
@@ -6,6 +7,7 @@ use std::fmt;
 pub enum ParseError {
     Malformed(String),
     InvalidInt(num::ParseIntError),
+    InvalidFloat(num::ParseFloatError),
 }
 
 impl fmt::Display for ParseError {
@@ -13,6 +15,7 @@ impl fmt::Display for ParseError {
         match *self {
             ParseError::Malformed(ref str) => write!(f, "Malformed {}!", str),
             ParseError::InvalidInt(ref err) => err.fmt(f),
+            ParseError::InvalidFloat(ref err) => err.fmt(f),
         }
     }
 }
@@ -22,6 +25,7 @@ impl error::Error for ParseError {
         match *self {
             ParseError::Malformed(_) => None,
             ParseError::InvalidInt(ref err) => Some(err),
+            ParseError::InvalidFloat(ref err) => Some(err),
         }
     }
 }
@@ -32,6 +36,12 @@ impl From<num::ParseIntError> for ParseError {
     }
 }
 
+impl From<num::ParseFloatError> for ParseError {
+    fn from(err: num::ParseFloatError) -> ParseError {
+        ParseError::InvalidFloat(err)
+    }
+}
+
 impl From<&str> for ParseError {
     fn from(err: &str) -> ParseError {
         ParseError::Malformed(err.into())
@@ -42,4 +52,28 @@ impl From<String> for ParseError {
     fn from(err: String) -> ParseError {
         ParseError::Malformed(err)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    name: String,
+    err: io::Error,
+}
+
+impl InputError {
+    pub fn new(name: impl fmt::Display, err: io::Error) -> InputError {
+        InputError { name: name.to_string(), err }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot open {}: {}", self.name, self.err)
+    }
+}
+
+impl error::Error for InputError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.err)
+    }
+}
@@ -1,36 +1,54 @@
 // Referenced https://docs.rs/rusttype/0.5.2/src/rusttype/geometry.rs.html
 // Other resources:
 //   https://crates.io/crates/euclid - https://doc.servo.org/src/euclid/point.rs.html
+//
+// Points and vectors are generic over their coordinate type, so the same API works for i64 or
+// floating-point geometry; Point and Vector are the i32 forms most puzzles use.
 mod point {
     use super::*;
     use std::fmt;
     use std::ops::{Add,AddAssign,Sub};
+    use num_traits::{Num, Zero};
     use regex::Regex;
     use std::str::FromStr;
     use crate::error::ParseError;
 
     #[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
-    pub struct Point {
-        pub x: i32,
-        pub y: i32,
+    pub struct Point2<T> {
+        pub x: T,
+        pub y: T,
     }
 
+    pub type Point = Point2<i32>;
+
     #[inline]
     pub const fn point(x: i32, y: i32) -> Point {
-        Point { x, y }
+        Point2 { x, y }
     }
 
+    // PartialOrd equivalents of std::cmp::min and max, so floats can be compared
+    fn min<T: PartialOrd>(a: T, b: T) -> T { if b < a { b } else { a } }
+    fn max<T: PartialOrd>(a: T, b: T) -> T { if b > a { b } else { a } }
 
     impl Point {
         pub const ORIGIN: Point = point(0, 0);
+    }
+
+    impl<T> Point2<T> {
+        #[inline]
+        pub const fn new(x: T, y: T) -> Point2<T> {
+            Point2 { x, y }
+        }
+    }
 
-        pub fn bounding_box(points: impl IntoIterator<Item = Point>) -> Option<(Point, Point)> {
+    impl<T: Copy + PartialOrd> Point2<T> {
+        pub fn bounding_box(points: impl IntoIterator<Item = Point2<T>>) -> Option<(Point2<T>, Point2<T>)> {
             points.into_iter().fold(None, |r , c|
                 match r {
-                    Some((min, max)) => {
+                    Some((min_p, max_p)) => {
                         Some((
-                            point(std::cmp::min(min.x, c.x), std::cmp::min(min.y, c.y)),
-                            point(std::cmp::max(max.x, c.x), std::cmp::max(max.y, c.y))
+                            Point2::new(min(min_p.x, c.x), min(min_p.y, c.y)),
+                            Point2::new(max(max_p.x, c.x), max(max_p.y, c.y))
                         ))
                     },
                     None => Some((c, c)),
@@ -38,64 +56,70 @@ mod point {
             )
         }
 
-        pub fn in_bounds(&self, min: Point, max: Point) -> bool {
+        pub fn in_bounds(&self, min: Point2<T>, max: Point2<T>) -> bool {
             assert!(min.x <= max.x);
             assert!(min.y <= max.y);
             min.x <= self.x && min.y <= self.y && max.x >= self.x && max.y >= self.y
         }
     }
 
-    impl Add<&Vector> for Point {
-        type Output = Point;
+    impl<T: Zero> Point2<T> {
+        pub fn origin() -> Point2<T> {
+            Point2::new(T::zero(), T::zero())
+        }
+    }
+
+    impl<T: Num + Copy> Add<&Vector2<T>> for Point2<T> {
+        type Output = Point2<T>;
 
-        fn add(self, vec: &Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y)
+        fn add(self, vec: &Vector2<T>) -> Point2<T> {
+            Point2::new(self.x + vec.x, self.y + vec.y)
         }
     }
 
-    impl Add<&Vector> for &Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<&Vector2<T>> for &Point2<T> {
+        type Output = Point2<T>;
 
-        fn add(self, vec: &Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y)
+        fn add(self, vec: &Vector2<T>) -> Point2<T> {
+            Point2::new(self.x + vec.x, self.y + vec.y)
         }
     }
 
-    impl Add<Vector> for &Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<Vector2<T>> for &Point2<T> {
+        type Output = Point2<T>;
 
-        fn add(self, vec: Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y)
+        fn add(self, vec: Vector2<T>) -> Point2<T> {
+            Point2::new(self.x + vec.x, self.y + vec.y)
         }
     }
 
-    impl Add<Vector> for Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<Vector2<T>> for Point2<T> {
+        type Output = Point2<T>;
 
-        fn add(self, vec: Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y)
+        fn add(self, vec: Vector2<T>) -> Point2<T> {
+            Point2::new(self.x + vec.x, self.y + vec.y)
         }
     }
 
-    impl AddAssign<Vector> for Point {
-        fn add_assign(&mut self, vec: Vector) {
-            *self = point(self.x + vec.x, self.y + vec.y);
+    impl<T: Num + Copy> AddAssign<Vector2<T>> for Point2<T> {
+        fn add_assign(&mut self, vec: Vector2<T>) {
+            *self = Point2::new(self.x + vec.x, self.y + vec.y);
         }
     }
 
-    impl Sub for Point {
-        type Output = Vector;
+    impl<T: Num + Copy> Sub for Point2<T> {
+        type Output = Vector2<T>;
 
-        fn sub(self, point: Point) -> Vector { vector(self.x - point.x, self.y - point.y) }
+        fn sub(self, point: Point2<T>) -> Vector2<T> { Vector2::new(self.x - point.x, self.y - point.y) }
     }
 
-    impl Sub for &Point {
-        type Output = Vector;
+    impl<T: Num + Copy> Sub for &Point2<T> {
+        type Output = Vector2<T>;
 
-        fn sub(self, point: &Point) -> Vector { vector(self.x - point.x, self.y - point.y) }
+        fn sub(self, point: &Point2<T>) -> Vector2<T> { Vector2::new(self.x - point.x, self.y - point.y) }
     }
 
-    impl FromStr for Point {
+    impl<T: FromStr> FromStr for Point2<T> where ParseError: From<T::Err> {
         type Err = ParseError;
 
         fn from_str(s: &str) -> Result<Self, ParseError> {
@@ -105,19 +129,19 @@ mod point {
             }
 
             let caps = regex_captures!(RE, s)?;
-            let x: i32 = capture_group!(caps, 1).trim().parse()?;
-            let y: i32 = capture_group!(caps, 2).trim().parse()?;
-            Ok(point(x, y))
+            let x: T = capture_group!(caps, 1).trim().parse()?;
+            let y: T = capture_group!(caps, 2).trim().parse()?;
+            Ok(Point2::new(x, y))
         }
     }
 
-    impl fmt::Debug for Point {
+    impl<T: fmt::Display> fmt::Debug for Point2<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "({}, {})", self.x, self.y)
         }
     }
 
-    impl fmt::Display for Point {
+    impl<T: fmt::Display> fmt::Display for Point2<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "{:?}", self)
         }
@@ -133,14 +157,18 @@ mod point {
             assert_eq!("-3,-4".parse::<Point>(), Ok(point(-3, -4)));
             assert_eq!("(40,30)".parse::<Point>(), Ok(point(40, 30)));
             assert_eq!("(-3, -5)".parse::<Point>(), Ok(point(-3, -5)));
+            assert_eq!("(1.5, -2)".parse::<Point2<f64>>(), Ok(Point2::new(1.5, -2.0)));
 
             assert!("abc".parse::<Point>().is_err());
+            assert!("(1.5, 2)".parse::<Point>().is_err());
         }
 
         #[test]
         fn bounding() {
             let points = vec!(point(1, 2), point(2, 3), point(0, 5));
             assert_eq!(Point::bounding_box(points), Some((point(0, 2), point(2, 5))));
+            let points = vec!(Point2::new(0.5, 2.0), Point2::new(-1.0, 2.5));
+            assert_eq!(Point2::bounding_box(points), Some((Point2::new(-1.0, 2.0), Point2::new(0.5, 2.5))));
         }
 
         #[test]
@@ -155,71 +183,111 @@ mod point {
         #[test]
         fn add() {
             assert_eq!(point(1, 0) + super::super::vector(2, 3), point(3, 3));
+            assert_eq!(Point2::<i64>::origin() + Vector2::new(5_000_000_000, 1), Point2::new(5_000_000_000, 1));
         }
         #[test]
         fn sub() {
             assert_eq!(point(3, 3) - point(1, 0), super::super::vector(2, 3));
+            assert_eq!(Point2::new(1.5, 1.0) - Point2::new(0.5, 2.0), Vector2::new(1.0, -1.0));
         }
     }
 }
-pub use self::point::{Point,point};
+pub use self::point::{Point,Point2,point};
 
 mod vector {
+    use std::cmp::Ordering;
     use std::fmt;
     use std::str::FromStr;
     use std::ops::Mul;
+    use num_integer::Integer;
+    use num_traits::{Num, Signed, ToPrimitive};
     use crate::error::ParseError;
 
     #[derive(Copy, Clone, PartialEq, Eq, Hash)]
-    pub struct Vector {
-        pub x: i32,
-        pub y: i32,
+    pub struct Vector2<T> {
+        pub x: T,
+        pub y: T,
     }
 
+    pub type Vector = Vector2<i32>;
+
     #[inline]
     pub const fn vector(x: i32, y: i32) -> Vector {
-        Vector { x, y }
+        Vector2 { x, y }
     }
 
-    impl Vector {
-        pub fn abs(&self) -> Vector {
-            vector(self.x.abs(), self.y.abs())
+    impl<T> Vector2<T> {
+        #[inline]
+        pub const fn new(x: T, y: T) -> Vector2<T> {
+            Vector2 { x, y }
         }
+    }
 
-        pub fn len(&self) -> f64 {
-            (self.x as f64).hypot(self.y as f64)
+    impl<T: Signed + Copy> Vector2<T> {
+        pub fn abs(&self) -> Vector2<T> {
+            Vector2::new(self.x.abs(), self.y.abs())
+        }
+
+        pub fn grid_len(&self) -> T {
+            self.x.abs() + self.y.abs()
+        }
+
+        // Orders vectors clockwise by angle, starting from up (0, -1) on a y-down grid, without
+        // resorting to floating point. Vectors pointing the same way are equal, regardless of length.
+        // See https://old.reddit.com/r/adventofcode/comments/e8r1jx
+        pub fn angle_cmp(&self, other: &Vector2<T>) -> Ordering where T: PartialOrd {
+            let zero = T::zero();
+            let (left, other_left) = (self.x < zero, other.x < zero);
+            if left != other_left { return left.cmp(&other_left); }
+            if self.x == zero && other.x == zero {
+                return self.y.signum().partial_cmp(&other.y.signum()).expect("Comparable");
+            }
+            zero.partial_cmp(&(self.x * other.y - self.y * other.x).signum()).expect("Comparable")
+        }
+    }
+
+    impl<T: Integer + Signed + Copy> Vector2<T> {
+        // The shortest vector with integer coordinates pointing the same way, e.g. (4, -6) becomes
+        // (2, -3). Every point along the vector's line of sight is a multiple of this vector.
+        pub fn primitive(&self) -> Vector2<T> {
+            if self.x.is_zero() && self.y.is_zero() { return *self; }
+            let gcd = self.x.gcd(&self.y);
+            Vector2::new(self.x / gcd, self.y / gcd)
         }
+    }
 
-        pub fn grid_len(&self) -> u32 {
-            (self.x.abs() + self.y.abs()) as u32
+    impl<T: ToPrimitive> Vector2<T> {
+        pub fn len(&self) -> f64 {
+            let (x, y) = (self.x.to_f64().expect("Representable"), self.y.to_f64().expect("Representable"));
+            x.hypot(y)
         }
     }
 
-    impl Mul<i32> for Vector {
-        type Output = Vector;
+    impl<T: Num + Copy> Mul<T> for Vector2<T> {
+        type Output = Vector2<T>;
 
-        fn mul(self, m: i32) -> Vector {
-            vector(self.x * m, self.y * m)
+        fn mul(self, m: T) -> Vector2<T> {
+            Vector2::new(self.x * m, self.y * m)
         }
     }
 
-    impl FromStr for Vector {
+    impl<T: FromStr> FromStr for Vector2<T> where ParseError: From<T::Err> {
         type Err = ParseError;
 
         fn from_str(s: &str) -> Result<Self, ParseError> {
             // Just reuse point's parser
-            let p: super::Point = s.parse()?;
-            Ok(vector(p.x, p.y))
+            let p = s.parse::<super::Point2<T>>()?;
+            Ok(Vector2::new(p.x, p.y))
         }
     }
 
-    impl fmt::Debug for Vector {
+    impl<T: fmt::Display> fmt::Debug for Vector2<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "({}, {})", self.x, self.y)
         }
     }
 
-    impl fmt::Display for Vector {
+    impl<T: fmt::Display> fmt::Display for Vector2<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "{:?}", self)
         }
@@ -234,11 +302,44 @@ mod vector {
         fn parse() {
             assert_eq!("3, 4".parse::<Vector>(), Ok(vector(3, 4)));
             assert_eq!("-3,-4".parse::<Vector>(), Ok(vector(-3, -4)));
+            assert_eq!("-3,4000000000".parse::<Vector2<i64>>(), Ok(Vector2::new(-3, 4_000_000_000)));
         }
 
         #[test]
         fn len() {
             assert_eq!(vector(3, -4).len(), 5_f64);
+            assert_eq!(Vector2::new(0.6, 0.8).len(), 1_f64);
+        }
+
+        parameterized_test::create!{ primitives, (v, expected), {
+            assert_eq!(v.primitive(), expected);
+        }}
+        primitives!{
+            zero: (vector(0, 0), vector(0, 0)),
+            axis: (vector(-10, 0), vector(-1, 0)),
+            coprime: (vector(10, 2), vector(5, 1)),
+            irreducible: (vector(5, 4), vector(5, 4)),
+            large: (vector(-4936, 6170), vector(-4, 5)),
+        }
+
+        #[test]
+        fn angles() {
+            // Clockwise from up
+            let order = [vector(0, -3), vector(1, -5), vector(2, -2), vector(1, 0), vector(4, 1), vector(0, 1),
+                vector(-3, 2), vector(-1, 0), vector(-1, -1)];
+            for (i, a) in order.iter().enumerate() {
+                for (j, b) in order.iter().enumerate() {
+                    assert_eq!(a.angle_cmp(b), i.cmp(&j), "{} vs {}", a, b);
+                }
+            }
+            assert_eq!(vector(2, -2).angle_cmp(&vector(5, -5)), Ordering::Equal);
+            assert_eq!(Vector2::new(0.5, -1.0).angle_cmp(&Vector2::new(0.5, 1.0)), Ordering::Less);
+        }
+
+        #[test]
+        fn mul() {
+            assert_eq!(vector(3, -4) * 2, vector(6, -8));
+            assert_eq!(Vector2::new(0.5, -1.5) * 2.0, Vector2::new(1.0, -3.0));
         }
 
         parameterized_test::create!{ grid_lens, (p1, p2, d), {
@@ -255,4 +356,960 @@ mod vector {
         }
     }
 }
-pub use self::vector::{Vector,vector};
+pub use self::vector::{Vector,Vector2,vector};
+
+mod sight {
+    use super::*;
+    use std::collections::HashMap;
+
+    // The points on a rasterized line from one point to another (inclusive), per Bresenham's line
+    // algorithm, see https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm#All_cases
+    pub struct Line {
+        cur: Point,
+        end: Point,
+        delta: Vector,
+        step: Vector,
+        err: i32,
+        done: bool,
+    }
+
+    #[allow(dead_code)]
+    pub fn line(from: Point, to: Point) -> Line {
+        let d = to - from;
+        let delta = vector(d.x.abs(), -d.y.abs());
+        Line { cur: from, end: to, delta, step: vector(d.x.signum(), d.y.signum()), err: delta.x + delta.y, done: false }
+    }
+
+    impl Iterator for Line {
+        type Item = Point;
+
+        fn next(&mut self) -> Option<Point> {
+            if self.done { return None; }
+            let ret = self.cur;
+            if self.cur == self.end {
+                self.done = true;
+                return Some(ret);
+            }
+            let e2 = 2 * self.err;
+            if e2 >= self.delta.y {
+                self.err += self.delta.y;
+                self.cur.x += self.step.x;
+            }
+            if e2 <= self.delta.x {
+                self.err += self.delta.x;
+                self.cur.y += self.step.y;
+            }
+            Some(ret)
+        }
+    }
+
+    // The points visible from the given point, i.e. those not hidden behind a nearer point in the
+    // same (exact) direction, ordered clockwise from up like Vector::angle_cmp()
+    pub fn visible(from: Point, points: impl IntoIterator<Item = Point>) -> Vec<Point> {
+        let mut nearest: HashMap<Vector, Point> = HashMap::new();
+        for p in points.into_iter().filter(|&p| p != from) {
+            let nearer = |q: &Point| (q - &from).grid_len() < (p - from).grid_len();
+            nearest.entry((p - from).primitive())
+                .and_modify(|q| if !nearer(q) { *q = p })
+                .or_insert(p);
+        }
+        let mut visible: Vec<_> = nearest.into_iter().collect();
+        visible.sort_by(|(a, _), (b, _)| a.angle_cmp(b));
+        visible.into_iter().map(|(_, p)| p).collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        parameterized_test::create!{ lines, (from, to, expected), {
+            let expected: Vec<Point> = expected;
+            assert_eq!(line(from, to).collect::<Vec<_>>(), expected);
+            // Ties can round differently in the other direction, but the line is the same length
+            let reversed: Vec<_> = line(to, from).collect();
+            assert_eq!((reversed.len(), reversed.first(), reversed.last()), (expected.len(), Some(&to), Some(&from)));
+        }}
+        lines! {
+            single: (point(1, 1), point(1, 1), vec!(point(1, 1))),
+            horizontal: (point(0, 0), point(3, 0), vec!(point(0, 0), point(1, 0), point(2, 0), point(3, 0))),
+            vertical: (point(2, 1), point(2, -1), vec!(point(2, 1), point(2, 0), point(2, -1))),
+            diagonal: (point(0, 0), point(-2, 2), vec!(point(0, 0), point(-1, 1), point(-2, 2))),
+            shallow: (point(0, 0), point(4, 2), vec!(point(0, 0), point(1, 1), point(2, 1), point(3, 2), point(4, 2))),
+        }
+
+        #[test]
+        fn steep_line() {
+            let points: Vec<_> = line(point(0, 0), point(1, 5)).collect();
+            assert_eq!(points.len(), 6);
+            assert_eq!(points.first(), Some(&point(0, 0)));
+            assert_eq!(points.last(), Some(&point(1, 5)));
+            assert!(points.windows(2).all(|w| (w[1] - w[0]).abs().y == 1 && (w[1] - w[0]).abs().x <= 1));
+        }
+
+        #[test]
+        fn visibility() {
+            let points = vec!(point(0, 0), point(0, -1), point(0, -3), point(2, 2), point(1, 1), point(-2, 0), point(3, -1));
+            assert_eq!(visible(point(0, 0), points), vec!(point(0, -1), point(3, -1), point(1, 1), point(-2, 0)));
+        }
+    }
+}
+#[allow(unused_imports)]
+pub use self::sight::line;
+pub use self::sight::visible;
+
+mod segment {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    // The straight line between two points, inclusive of both ends
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    pub struct Segment {
+        pub start: Point,
+        pub end: Point,
+    }
+
+    // Cross product of two vectors, widened so the products of large coordinates can't overflow
+    fn cross(a: Vector, b: Vector) -> i64 {
+        a.x as i64 * b.y as i64 - a.y as i64 * b.x as i64
+    }
+
+    impl Segment {
+        pub fn new(start: Point, end: Point) -> Segment {
+            Segment { start, end }
+        }
+
+        pub fn grid_len(&self) -> i32 {
+            (self.end - self.start).grid_len()
+        }
+
+        pub fn contains(&self, p: Point) -> bool {
+            let (min, max) = Point::bounding_box(vec!(self.start, self.end)).expect("Non-empty");
+            cross(self.end - self.start, p - self.start) == 0 && p.in_bounds(min, max)
+        }
+
+        // The points with integer coordinates that lie on both segments; usually zero or one, but
+        // overlapping collinear segments share every point along the overlap. A crossing that falls
+        // between integer coordinates isn't included.
+        pub fn intersections(&self, other: &Segment) -> Vec<Point> {
+            let (r, s) = (self.end - self.start, other.end - other.start);
+            if r == vector(0, 0) {
+                return if other.contains(self.start) { vec!(self.start) } else { vec!() };
+            }
+            let offset = other.start - self.start;
+            let denom = cross(r, s);
+            if denom == 0 {
+                if cross(offset, r) != 0 { return vec!(); } // parallel
+                // Collinear; every integer point on the line is a multiple of the primitive direction
+                let step = r.primitive();
+                let steps_to = |p: Point| {
+                    let v = p - self.start;
+                    (v.x * step.x + v.y * step.y) / (step.x * step.x + step.y * step.y)
+                };
+                let (a, b) = (steps_to(other.start), steps_to(other.end));
+                let (lo, hi) = (std::cmp::max(0, std::cmp::min(a, b)), std::cmp::min(steps_to(self.end), std::cmp::max(a, b)));
+                return (lo..=hi).map(|i| self.start + step * i).collect();
+            }
+            // Solve start + t*r = other.start + u*s, for t and u in [0, 1]
+            let (t, u) = (cross(offset, s), cross(offset, r));
+            let within = |n: i64| if denom > 0 { 0 <= n && n <= denom } else { denom <= n && n <= 0 };
+            if !within(t) || !within(u) { return vec!(); }
+            let (x, y) = (r.x as i64 * t, r.y as i64 * t);
+            if x % denom != 0 || y % denom != 0 { return vec!(); }
+            vec!(self.start + vector((x / denom) as i32, (y / denom) as i32))
+        }
+    }
+
+    // A point shared by two paths, along with the distance along each path to first reach it
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct Crossing {
+        pub point: Point,
+        pub distances: (i32, i32),
+    }
+
+    // A path of connected segments. Lengths and distances along the path are measured in grid
+    // (Manhattan) steps, which is exact for axis-aligned and diagonal segments.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Polyline {
+        vertices: Vec<Point>,
+    }
+
+    #[allow(dead_code)]
+    impl Polyline {
+        pub fn new(start: Point) -> Polyline {
+            Polyline { vertices: vec!(start) }
+        }
+
+        // Extends the path from its current end
+        pub fn push(&mut self, vec: Vector) {
+            let end = *self.vertices.last().expect("Non-empty");
+            self.vertices.push(end + vec);
+        }
+
+        pub fn vertices(&self) -> &[Point] { &self.vertices }
+
+        pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+            self.vertices.windows(2).map(|w| Segment::new(w[0], w[1]))
+        }
+
+        pub fn grid_len(&self) -> i32 {
+            self.segments().map(|s| s.grid_len()).sum()
+        }
+
+        // Each segment along with the distance travelled before it starts
+        fn distanced_segments(&self) -> impl Iterator<Item = (i32, Segment)> + '_ {
+            self.segments().scan(0, |dist, s| {
+                let start = *dist;
+                *dist += s.grid_len();
+                Some((start, s))
+            })
+        }
+
+        // The distance along the path to the first time it reaches the given point, if ever
+        pub fn distance_to(&self, p: Point) -> Option<i32> {
+            if self.vertices.len() == 1 && self.vertices[0] == p { return Some(0); }
+            self.distanced_segments()
+                .find(|(_, s)| s.contains(p))
+                .map(|(d, s)| d + (p - s.start).grid_len())
+        }
+
+        // Every point where the two paths meet, ordered by point
+        pub fn intersections(&self, other: &Polyline) -> Vec<Crossing> {
+            let mut crossings: BTreeMap<Point, (i32, i32)> = BTreeMap::new();
+            for (d1, s1) in self.distanced_segments() {
+                for (d2, s2) in other.distanced_segments() {
+                    for p in s1.intersections(&s2) {
+                        let distances = (d1 + (p - s1.start).grid_len(), d2 + (p - s2.start).grid_len());
+                        let entry = crossings.entry(p).or_insert(distances);
+                        *entry = (std::cmp::min(entry.0, distances.0), std::cmp::min(entry.1, distances.1));
+                    }
+                }
+            }
+            crossings.into_iter().map(|(point, distances)| Crossing { point, distances }).collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        parameterized_test::create!{ segment_intersections, (a, b, expected), {
+            let (a, b): (Segment, Segment) = (a, b);
+            let expected: Vec<Point> = expected;
+            assert_eq!(a.intersections(&b), expected);
+            let mut reversed = b.intersections(&a);
+            reversed.sort();
+            let mut sorted = expected.clone();
+            sorted.sort();
+            assert_eq!(reversed, sorted);
+        }}
+        segment_intersections! {
+            crossing: (Segment::new(point(0, 0), point(4, 0)), Segment::new(point(2, -2), point(2, 2)), vec!(point(2, 0))),
+            touching: (Segment::new(point(0, 0), point(4, 0)), Segment::new(point(4, 0), point(4, 2)), vec!(point(4, 0))),
+            apart: (Segment::new(point(0, 0), point(4, 0)), Segment::new(point(5, -2), point(5, 2)), vec!()),
+            parallel: (Segment::new(point(0, 0), point(4, 0)), Segment::new(point(0, 1), point(4, 1)), vec!()),
+            overlapping: (Segment::new(point(0, 0), point(4, 0)), Segment::new(point(6, 0), point(2, 0)),
+                vec!(point(2, 0), point(3, 0), point(4, 0))),
+            collinear_apart: (Segment::new(point(0, 0), point(2, 2)), Segment::new(point(3, 3), point(5, 5)), vec!()),
+            diagonal: (Segment::new(point(0, 0), point(4, 4)), Segment::new(point(0, 4), point(4, 0)), vec!(point(2, 2))),
+            between_points: (Segment::new(point(0, 0), point(1, 1)), Segment::new(point(0, 1), point(1, 0)), vec!()),
+            degenerate: (Segment::new(point(1, 1), point(1, 1)), Segment::new(point(0, 0), point(3, 3)), vec!(point(1, 1))),
+        }
+
+        fn wire(start: Point, moves: &[Vector]) -> Polyline {
+            let mut wire = Polyline::new(start);
+            moves.iter().for_each(|&v| wire.push(v));
+            wire
+        }
+
+        #[test]
+        fn polyline() {
+            let wire = wire(Point::ORIGIN, &[vector(4, 0), vector(0, -2), vector(-2, 0), vector(0, 3)]);
+            assert_eq!(wire.vertices(), &[point(0, 0), point(4, 0), point(4, -2), point(2, -2), point(2, 1)]);
+            assert_eq!(wire.grid_len(), 11);
+            assert_eq!(wire.distance_to(point(3, 0)), Some(3));
+            // Visited twice; the first visit counts
+            assert_eq!(wire.distance_to(point(2, 0)), Some(2));
+            assert_eq!(wire.distance_to(point(5, 0)), None);
+        }
+
+        #[test]
+        fn crossings() {
+            // From the Day 3 example
+            let one = wire(Point::ORIGIN, &[vector(8, 0), vector(0, -5), vector(-5, 0), vector(0, 3)]);
+            let two = wire(Point::ORIGIN, &[vector(0, -7), vector(6, 0), vector(0, 4), vector(-4, 0)]);
+            assert_eq!(one.intersections(&two), vec!(
+                Crossing { point: point(0, 0), distances: (0, 0) },
+                Crossing { point: point(3, -3), distances: (20, 20) },
+                Crossing { point: point(6, -5), distances: (15, 15) },
+            ));
+        }
+    }
+}
+pub use self::segment::{Crossing, Polyline};
+#[allow(unused_imports)]
+pub use self::segment::Segment;
+
+mod grid {
+    use super::*;
+    use std::fmt;
+    use std::ops::{Index, IndexMut};
+    use crate::error::ParseError;
+
+    // A dense, rectangular grid of cells covering the points from origin to origin+(width-1,height-1)
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    pub struct Grid<T> {
+        origin: Point,
+        width: usize,
+        height: usize,
+        cells: Vec<T>,
+    }
+
+    #[allow(dead_code)]
+    impl<T: Clone> Grid<T> {
+        pub fn new(origin: Point, width: usize, height: usize, fill: T) -> Grid<T> {
+            Grid { origin, width, height, cells: vec!(fill; width * height) }
+        }
+    }
+
+    #[allow(dead_code)]
+    impl<T> Grid<T> {
+        pub fn from_fn(origin: Point, width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Grid<T> {
+            let cells = (0..height as i32)
+                .flat_map(|y| (0..width as i32).map(move |x| origin + vector(x, y)))
+                .map(&mut f)
+                .collect();
+            Grid { origin, width, height, cells }
+        }
+
+        // Parses a rectangular character map with its top-left corner at the origin, mapping each
+        // character to a cell. Trailing blank lines are ignored.
+        pub fn parse(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Result<Grid<T>, ParseError> {
+            let lines: Vec<_> = input.trim_end_matches('\n').lines().collect();
+            let width = lines.first().map(|l| l.chars().count()).unwrap_or(0);
+            let mut cells = Vec::with_capacity(width * lines.len());
+            for line in &lines {
+                if line.chars().count() != width {
+                    return Err(format!("row, expected {} columns: {}", width, line).into());
+                }
+                for c in line.chars() {
+                    cells.push(f(c).ok_or_else(|| format!("cell: {:?}", c))?);
+                }
+            }
+            Ok(Grid { origin: Point::ORIGIN, width, height: lines.len(), cells })
+        }
+
+        pub fn width(&self) -> usize { self.width }
+
+        pub fn height(&self) -> usize { self.height }
+
+        // The inclusive top-left and bottom-right corners, consistent with Point::bounding_box()
+        pub fn bounds(&self) -> (Point, Point) {
+            (self.origin, self.origin + vector(self.width as i32 - 1, self.height as i32 - 1))
+        }
+
+        fn index_of(&self, p: Point) -> Option<usize> {
+            let offset = p - self.origin;
+            if offset.x < 0 || offset.y < 0 || offset.x as usize >= self.width || offset.y as usize >= self.height {
+                return None;
+            }
+            Some(offset.y as usize * self.width + offset.x as usize)
+        }
+
+        pub fn contains(&self, p: Point) -> bool {
+            self.index_of(p).is_some()
+        }
+
+        pub fn get(&self, p: Point) -> Option<&T> {
+            self.index_of(p).map(|i| &self.cells[i])
+        }
+
+        pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+            self.index_of(p).map(move |i| &mut self.cells[i])
+        }
+
+        // Every point in the grid, in row-major order
+        pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+            (0..self.height as i32).flat_map(move |y| (0..self.width as i32).map(move |x| self.origin + vector(x, y)))
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+            self.points().zip(self.cells.iter())
+        }
+
+        // The in-bounds orthogonally adjacent points
+        pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+            Direction4::ALL.iter().map(move |d| p + d.vector()).filter(move |&n| self.contains(n))
+        }
+
+        // The in-bounds orthogonally and diagonally adjacent points
+        pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+            Direction8::ALL.iter().map(move |d| p + d.vector()).filter(move |&n| self.contains(n))
+        }
+
+        pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+            // chunks() panics on zero, but an empty grid has no rows regardless
+            self.cells.chunks(std::cmp::max(self.width, 1))
+        }
+
+        // The cells in the given column, which is relative to the origin like rows() is
+        pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+            assert!(x < self.width, "Column {} out of bounds", x);
+            self.cells.iter().skip(x).step_by(self.width)
+        }
+
+        pub fn render(&self, f: impl Fn(&T) -> char) -> String {
+            self.rows().map(|row| row.iter().map(&f).collect::<String>()).collect::<Vec<_>>().join("\n")
+        }
+    }
+
+    impl<T> Index<Point> for Grid<T> {
+        type Output = T;
+
+        fn index(&self, p: Point) -> &T {
+            self.get(p).unwrap_or_else(|| panic!("{} is outside {:?}", p, self.bounds()))
+        }
+    }
+
+    impl<T> IndexMut<Point> for Grid<T> {
+        fn index_mut(&mut self, p: Point) -> &mut T {
+            let bounds = self.bounds();
+            self.get_mut(p).unwrap_or_else(|| panic!("{} is outside {:?}", p, bounds))
+        }
+    }
+
+    impl<T: fmt::Display> fmt::Display for Grid<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for (i, row) in self.rows().enumerate() {
+                if i > 0 { writeln!(f)?; }
+                for cell in row {
+                    write!(f, "{}", cell)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<T: fmt::Debug> fmt::Debug for Grid<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Grid {:?}: ", self.bounds())?;
+            f.debug_list().entries(self.rows()).finish()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn sample() -> Grid<char> {
+            Grid::parse("#..\n.#.\n..#\n.##\n", Some).unwrap()
+        }
+
+        #[test]
+        fn parse() {
+            let grid = sample();
+            assert_eq!((grid.width(), grid.height()), (3, 4));
+            assert_eq!(grid.bounds(), (point(0, 0), point(2, 3)));
+            assert_eq!(grid[point(1, 1)], '#');
+            assert_eq!(grid.to_string(), "#..\n.#.\n..#\n.##");
+
+            assert!(Grid::parse("..\n...", Some).is_err());
+            assert!(Grid::parse("#?", |c| if c == '#' { Some(true) } else { None }).is_err());
+        }
+
+        #[test]
+        fn offset_origin() {
+            let mut grid = Grid::new(point(-2, -1), 3, 2, 0);
+            assert_eq!(grid.bounds(), (point(-2, -1), point(0, 0)));
+            grid[point(-2, -1)] = 5;
+            grid[point(0, 0)] = 7;
+            assert_eq!(grid.get(point(1, 0)), None);
+            assert_eq!(grid.to_string(), "500\n007");
+
+            let grid = Grid::from_fn(point(3, 4), 2, 2, |p| p.x * p.y);
+            assert_eq!(grid.iter().collect::<Vec<_>>(),
+                       vec!((point(3, 4), &12), (point(4, 4), &16), (point(3, 5), &15), (point(4, 5), &20)));
+        }
+
+        parameterized_test::create!{ neighbors, (p, four, eight), {
+            let grid = sample();
+            assert_eq!(grid.neighbors4(p).count(), four);
+            assert_eq!(grid.neighbors8(p).count(), eight);
+        }}
+        neighbors! {
+            corner: (point(0, 0), 2, 3),
+            edge: (point(0, 1), 3, 5),
+            middle: (point(1, 1), 4, 8),
+            outside: (point(5, 5), 0, 0),
+        }
+
+        #[test]
+        fn rows_and_columns() {
+            let grid = sample();
+            assert_eq!(grid.rows().nth(3), Some(&['.', '#', '#'][..]));
+            assert_eq!(grid.column(1).collect::<String>(), ".#.#");
+            assert_eq!(grid.render(|&c| if c == '#' { '█' } else { ' ' }), "█  \n █ \n  █\n ██");
+        }
+    }
+}
+pub use self::grid::Grid;
+
+mod sparse_grid {
+    use super::*;
+    use std::collections::HashMap;
+    use std::iter::FromIterator;
+
+    // An unbounded grid of cells, of which only those that have been set are stored. The bounding
+    // box of the set cells is tracked as cells are inserted (it never shrinks).
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct SparseGrid<T> {
+        cells: HashMap<Point, T>,
+        default: Option<T>,
+        bounds: Option<(Point, Point)>,
+    }
+
+    #[allow(dead_code)]
+    impl<T> SparseGrid<T> {
+        pub fn new() -> SparseGrid<T> {
+            SparseGrid { cells: HashMap::new(), default: None, bounds: None }
+        }
+
+        // get() and render() will use this value for any cell that hasn't been set
+        pub fn with_default(default: T) -> SparseGrid<T> {
+            SparseGrid { cells: HashMap::new(), default: Some(default), bounds: None }
+        }
+
+        pub fn insert(&mut self, p: Point, value: T) -> Option<T> {
+            self.bounds = Some(match self.bounds {
+                Some((min, max)) =>
+                    (point(min.x.min(p.x), min.y.min(p.y)), point(max.x.max(p.x), max.y.max(p.y))),
+                None => (p, p),
+            });
+            self.cells.insert(p, value)
+        }
+
+        pub fn get(&self, p: Point) -> Option<&T> {
+            self.cells.get(&p).or(self.default.as_ref())
+        }
+
+        // Whether the cell has been set, regardless of any default
+        pub fn contains(&self, p: Point) -> bool {
+            self.cells.contains_key(&p)
+        }
+
+        pub fn len(&self) -> usize { self.cells.len() }
+
+        pub fn is_empty(&self) -> bool { self.cells.is_empty() }
+
+        // The set cells, in arbitrary order
+        pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+            self.cells.iter().map(|(&p, v)| (p, v))
+        }
+
+        // The inclusive bounding box of all cells that have been set
+        pub fn bounds(&self) -> Option<(Point, Point)> {
+            self.bounds
+        }
+
+        // Renders every cell within the bounding box, see render_in()
+        pub fn render(&self, f: impl Fn(Point, Option<&T>) -> char) -> String {
+            match self.bounds {
+                Some(bounds) => self.render_in(bounds, f),
+                None => String::new(),
+            }
+        }
+
+        // Renders every cell within the (inclusive) viewport, one line per row. The function is
+        // passed the cell's value, as returned by get().
+        pub fn render_in(&self, viewport: (Point, Point), f: impl Fn(Point, Option<&T>) -> char) -> String {
+            let (min, max) = viewport;
+            (min.y..=max.y)
+                .map(|y| (min.x..=max.x).map(|x| { let p = point(x, y); f(p, self.get(p)) }).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    #[allow(dead_code)]
+    impl<T: Clone> SparseGrid<T> {
+        // An unset cell is first set to the default, if there is one, so that writes agree with get()
+        pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+            if !self.cells.contains_key(&p) {
+                if let Some(default) = self.default.clone() {
+                    self.insert(p, default);
+                }
+            }
+            self.cells.get_mut(&p)
+        }
+    }
+
+    impl<T> Default for SparseGrid<T> {
+        fn default() -> Self { SparseGrid::new() }
+    }
+
+    impl<T> Extend<(Point, T)> for SparseGrid<T> {
+        fn extend<I: IntoIterator<Item = (Point, T)>>(&mut self, iter: I) {
+            for (p, v) in iter {
+                self.insert(p, v);
+            }
+        }
+    }
+
+    impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+        fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+            let mut grid = SparseGrid::new();
+            grid.extend(iter);
+            grid
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn bounds() {
+            let mut grid = SparseGrid::new();
+            assert_eq!(grid.bounds(), None);
+            assert_eq!(grid.render(|_, _| '?'), "");
+            grid.insert(point(2, 3), 'a');
+            assert_eq!(grid.bounds(), Some((point(2, 3), point(2, 3))));
+            grid.insert(point(-1, 5), 'b');
+            grid.insert(point(0, 4), 'c');
+            assert_eq!(grid.bounds(), Some((point(-1, 3), point(2, 5))));
+            assert_eq!(grid.len(), 3);
+        }
+
+        #[test]
+        fn get() {
+            let mut grid: SparseGrid<_> = [(point(0, 0), 1)].iter().cloned().collect();
+            assert_eq!(grid.get(point(0, 0)), Some(&1));
+            assert_eq!(grid.get(point(1, 1)), None);
+            assert_eq!(grid.get_mut(point(1, 1)), None);
+            *grid.get_mut(point(0, 0)).expect("Set") += 1;
+            assert_eq!(grid.get(point(0, 0)), Some(&2));
+            assert!(grid.contains(point(0, 0)));
+            assert!(!grid.contains(point(1, 1)));
+        }
+
+        #[test]
+        fn defaults() {
+            let mut grid = SparseGrid::with_default(0);
+            grid.insert(point(0, 0), 1);
+            assert_eq!(grid.get(point(0, 0)), Some(&1));
+            assert_eq!(grid.get(point(1, 1)), Some(&0));
+            assert!(!grid.contains(point(1, 1)));
+            assert_eq!(grid.render(|_, v| char::from(b'0' + *v.unwrap() as u8)), "1");
+
+            *grid.get_mut(point(1, 1)).expect("Has default") += 2;
+            assert!(grid.contains(point(1, 1)));
+            assert_eq!(grid.bounds(), Some((point(0, 0), point(1, 1))));
+            assert_eq!(grid.render(|_, v| char::from(b'0' + *v.unwrap() as u8)), "10\n02");
+        }
+
+        #[test]
+        fn render() {
+            let grid: SparseGrid<_> = [(point(-1, 0), true), (point(1, 1), false)].iter().cloned().collect();
+            let draw = |_, v: Option<&bool>| match v { Some(true) => '#', Some(false) => '.', None => ' ' };
+            assert_eq!(grid.render(draw), "#  \n  .");
+            assert_eq!(grid.render_in((point(0, 0), point(2, 1)), draw), "   \n . ");
+            assert_eq!(grid.render(|p, _| if p == point(0, 0) { '@' } else { '_' }), "_@_\n___");
+        }
+    }
+}
+pub use self::sparse_grid::SparseGrid;
+
+mod direction {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::fmt;
+    use std::str::FromStr;
+    use crate::error::ParseError;
+
+    // Directions are relative to a grid where y increases downwards, as in the puzzles' maps, so
+    // North (or Up) is (0, -1)
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum Direction4 {
+        North,
+        East,
+        South,
+        West,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    pub enum Turn {
+        Straight,
+        Right,
+        Reverse,
+        Left,
+    }
+
+    #[allow(dead_code)]
+    impl Turn {
+        // The number of clockwise quarter-turns
+        fn quarters(&self) -> u8 {
+            match self {
+                Turn::Straight => 0,
+                Turn::Right => 1,
+                Turn::Reverse => 2,
+                Turn::Left => 3,
+            }
+        }
+
+        fn from_quarters(quarters: u8) -> Turn {
+            [Turn::Straight, Turn::Right, Turn::Reverse, Turn::Left][(quarters % 4) as usize]
+        }
+    }
+
+    impl FromStr for Turn {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, ParseError> {
+            match s {
+                "S" => Ok(Turn::Straight),
+                "R" => Ok(Turn::Right),
+                "B" => Ok(Turn::Reverse),
+                "L" => Ok(Turn::Left),
+                _ => Err(format!("turn: {}", s).into()),
+            }
+        }
+    }
+
+    impl fmt::Display for Turn {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let s = match self {
+                Turn::Straight => "S",
+                Turn::Right => "R",
+                Turn::Reverse => "B",
+                Turn::Left => "L",
+            };
+            write!(f, "{}", s)
+        }
+    }
+
+    #[allow(dead_code)]
+    impl Direction4 {
+        pub const ALL: [Direction4; 4] = [Direction4::North, Direction4::East, Direction4::South, Direction4::West];
+
+        pub fn vector(&self) -> Vector {
+            match self {
+                Direction4::North => vector(0, -1),
+                Direction4::East => vector(1, 0),
+                Direction4::South => vector(0, 1),
+                Direction4::West => vector(-1, 0),
+            }
+        }
+
+        // The direction of a unit vector, if it's one of the four cardinal directions
+        pub fn from_vector(vec: Vector) -> Option<Direction4> {
+            Direction4::ALL.iter().cloned().find(|d| d.vector() == vec)
+        }
+
+        fn index(&self) -> u8 {
+            Direction4::ALL.iter().position(|d| d == self).expect("All directions") as u8
+        }
+
+        pub fn turn(&self, turn: Turn) -> Direction4 {
+            Direction4::ALL[((self.index() + turn.quarters()) % 4) as usize]
+        }
+
+        pub fn left(&self) -> Direction4 { self.turn(Turn::Left) }
+
+        pub fn right(&self) -> Direction4 { self.turn(Turn::Right) }
+
+        pub fn reverse(&self) -> Direction4 { self.turn(Turn::Reverse) }
+
+        // The turn needed to face the other direction
+        pub fn turn_to(&self, other: Direction4) -> Turn {
+            Turn::from_quarters(other.index() + 4 - self.index())
+        }
+
+        pub fn arrow(&self) -> char {
+            match self {
+                Direction4::North => '^',
+                Direction4::East => '>',
+                Direction4::South => 'v',
+                Direction4::West => '<',
+            }
+        }
+    }
+
+    // Accepts U/D/L/R, N/S/E/W, and ^/v/</> characters
+    impl TryFrom<char> for Direction4 {
+        type Error = ParseError;
+
+        fn try_from(c: char) -> Result<Self, ParseError> {
+            match c {
+                'U' | 'N' | '^' => Ok(Direction4::North),
+                'R' | 'E' | '>' => Ok(Direction4::East),
+                'D' | 'S' | 'v' => Ok(Direction4::South),
+                'L' | 'W' | '<' => Ok(Direction4::West),
+                _ => Err(format!("direction: {}", c).into()),
+            }
+        }
+    }
+
+    impl FromStr for Direction4 {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, ParseError> {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Direction4::try_from(c),
+                _ => Err(format!("direction: {}", s).into()),
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum Direction8 {
+        North,
+        NorthEast,
+        East,
+        SouthEast,
+        South,
+        SouthWest,
+        West,
+        NorthWest,
+    }
+
+    #[allow(dead_code)]
+    impl Direction8 {
+        pub const ALL: [Direction8; 8] = [Direction8::North, Direction8::NorthEast, Direction8::East,
+            Direction8::SouthEast, Direction8::South, Direction8::SouthWest, Direction8::West, Direction8::NorthWest];
+
+        pub fn vector(&self) -> Vector {
+            match self {
+                Direction8::North => vector(0, -1),
+                Direction8::NorthEast => vector(1, -1),
+                Direction8::East => vector(1, 0),
+                Direction8::SouthEast => vector(1, 1),
+                Direction8::South => vector(0, 1),
+                Direction8::SouthWest => vector(-1, 1),
+                Direction8::West => vector(-1, 0),
+                Direction8::NorthWest => vector(-1, -1),
+            }
+        }
+
+        pub fn from_vector(vec: Vector) -> Option<Direction8> {
+            Direction8::ALL.iter().cloned().find(|d| d.vector() == vec)
+        }
+
+        fn index(&self) -> usize {
+            Direction8::ALL.iter().position(|d| d == self).expect("All directions")
+        }
+
+        // Rotates clockwise by the given number of eighth-turns (45 degrees each); negative values
+        // rotate counter-clockwise
+        pub fn rotate(&self, eighths: i32) -> Direction8 {
+            Direction8::ALL[(self.index() as i32 + eighths).rem_euclid(8) as usize]
+        }
+
+        pub fn reverse(&self) -> Direction8 { self.rotate(4) }
+    }
+
+    impl From<Direction4> for Direction8 {
+        fn from(dir: Direction4) -> Direction8 {
+            Direction8::from_vector(dir.vector()).expect("Cardinal directions are shared")
+        }
+    }
+
+    // A position and facing, for turtle-style movement
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    pub struct Heading {
+        pub pos: Point,
+        pub dir: Direction4,
+    }
+
+    impl Heading {
+        pub fn new(pos: Point, dir: Direction4) -> Heading {
+            Heading { pos, dir }
+        }
+
+        // The position one step ahead
+        pub fn ahead(&self) -> Point {
+            self.pos + self.dir.vector()
+        }
+
+        pub fn advance(&mut self, steps: i32) {
+            self.pos += self.dir.vector() * steps;
+        }
+
+        pub fn turn(&mut self, turn: Turn) {
+            self.dir = self.dir.turn(turn);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        parameterized_test::create!{ parse, (c, dir), {
+            assert_eq!(Direction4::try_from(c), Ok(dir));
+            assert_eq!(c.to_string().parse::<Direction4>(), Ok(dir));
+        }}
+        parse! {
+            up: ('U', Direction4::North),
+            north: ('N', Direction4::North),
+            arrow_up: ('^', Direction4::North),
+            right: ('R', Direction4::East),
+            arrow_down: ('v', Direction4::South),
+            west: ('W', Direction4::West),
+        }
+
+        #[test]
+        fn parse_invalid() {
+            assert!(Direction4::try_from('x').is_err());
+            assert!("UR".parse::<Direction4>().is_err());
+            assert!("X".parse::<Turn>().is_err());
+        }
+
+        #[test]
+        fn turns() {
+            for &t in &[Turn::Straight, Turn::Left, Turn::Right, Turn::Reverse] {
+                assert_eq!(t.to_string().parse::<Turn>(), Ok(t));
+            }
+        }
+
+        #[test]
+        fn rotation() {
+            use Direction4::*;
+            assert_eq!(North.right(), East);
+            assert_eq!(North.left(), West);
+            assert_eq!(West.right(), North);
+            assert_eq!(East.reverse(), West);
+            for &d in &Direction4::ALL {
+                assert_eq!(d.left().right(), d);
+                assert_eq!(Direction4::from_vector(d.vector()), Some(d));
+                for &t in &[Turn::Straight, Turn::Left, Turn::Right, Turn::Reverse] {
+                    assert_eq!(d.turn_to(d.turn(t)), t);
+                }
+            }
+            assert_eq!(Direction4::from_vector(vector(1, 1)), None);
+        }
+
+        #[test]
+        fn rotation8() {
+            use Direction8::*;
+            assert_eq!(North.rotate(1), NorthEast);
+            assert_eq!(North.rotate(-1), NorthWest);
+            assert_eq!(SouthWest.reverse(), NorthEast);
+            assert_eq!(Direction8::from(Direction4::West), West);
+            for &d in &Direction8::ALL {
+                assert_eq!(d.rotate(-3).rotate(3), d);
+                assert_eq!(Direction8::from_vector(d.vector()), Some(d));
+            }
+        }
+
+        #[test]
+        fn heading() {
+            let mut heading = Heading::new(Point::ORIGIN, Direction4::North);
+            assert_eq!(heading.ahead(), point(0, -1));
+            heading.advance(3);
+            heading.turn(Turn::Right);
+            heading.advance(2);
+            assert_eq!(heading, Heading::new(point(2, -3), Direction4::East));
+        }
+    }
+}
+pub use self::direction::{Direction4, Direction8, Heading, Turn};
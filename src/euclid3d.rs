@@ -1,102 +1,149 @@
 // Referenced https://docs.rs/rusttype/0.5.2/src/rusttype/geometry.rs.html
 // Other resources:
 //   https://crates.io/crates/euclid - https://doc.servo.org/src/euclid/point.rs.html
+//
+// Like euclid, points and vectors are generic over their coordinate type; Point and Vector are the
+// i32 forms.
 mod point {
     use super::*;
     use std::fmt;
     use std::ops::{Add,AddAssign,Sub};
     use regex::Regex;
     use std::str::FromStr;
-    use std::cmp;
+    use num_traits::Num;
     use crate::error::ParseError;
 
     #[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
-    pub struct Point {
-        pub x: i32,
-        pub y: i32,
-        pub z: i32,
+    pub struct Point3<T> {
+        pub x: T,
+        pub y: T,
+        pub z: T,
     }
 
+    pub type Point = Point3<i32>;
+
     #[inline]
     pub const fn point(x: i32, y: i32, z: i32) -> Point {
-        Point { x, y, z }
+        Point3 { x, y, z }
     }
 
+    // PartialOrd equivalents of std::cmp::min and max, so floats can be compared
+    fn min<T: PartialOrd>(a: T, b: T) -> T { if b < a { b } else { a } }
+    fn max<T: PartialOrd>(a: T, b: T) -> T { if b > a { b } else { a } }
+
     impl Point {
         pub const ORIGIN: Point = point(0, 0, 0);
 
-        pub fn bounding_box(points: impl IntoIterator<Item = Point>) -> Option<(Point, Point)> {
+        // The orthogonally adjacent points
+        pub fn neighbors6(&self) -> impl Iterator<Item = Point> {
+            let p = *self;
+            NEIGHBORS6.iter().map(move |v| p + v)
+        }
+
+        // Every adjacent point, including diagonals
+        pub fn neighbors26(&self) -> impl Iterator<Item = Point> {
+            let p = *self;
+            NEIGHBORS26.iter().map(move |v| p + v)
+        }
+    }
+
+    const NEIGHBORS6: [Vector; 6] = [vector(0, 0, -1), vector(0, -1, 0), vector(-1, 0, 0),
+        vector(1, 0, 0), vector(0, 1, 0), vector(0, 0, 1)];
+
+    lazy_static! {
+        static ref NEIGHBORS26: Vec<Vector> = (-1..=1)
+            .flat_map(|z| (-1..=1).flat_map(move |y| (-1..=1).map(move |x| vector(x, y, z))))
+            .filter(|&v| v != vector(0, 0, 0))
+            .collect();
+    }
+
+    impl<T> Point3<T> {
+        #[inline]
+        pub const fn new(x: T, y: T, z: T) -> Point3<T> {
+            Point3 { x, y, z }
+        }
+    }
+
+    impl<T: Copy + PartialOrd> Point3<T> {
+        pub fn bounding_box(points: impl IntoIterator<Item = Point3<T>>) -> Option<(Point3<T>, Point3<T>)> {
             points.into_iter().fold(None, |r , c|
                 match r {
-                    Some((min, max)) => {
+                    Some((min_p, max_p)) => {
                         Some((
-                            point(cmp::min(min.x, c.x), cmp::min(min.y, c.y), cmp::min(min.z, c.z)),
-                            point(cmp::max(max.x, c.x), cmp::max(max.y, c.y), cmp::max(max.z, c.z))
+                            Point3::new(min(min_p.x, c.x), min(min_p.y, c.y), min(min_p.z, c.z)),
+                            Point3::new(max(max_p.x, c.x), max(max_p.y, c.y), max(max_p.z, c.z))
                         ))
                     },
                     None => Some((c, c)),
                 }
             )
         }
+
+        pub fn in_bounds(&self, min: Point3<T>, max: Point3<T>) -> bool {
+            assert!(min.x <= max.x);
+            assert!(min.y <= max.y);
+            assert!(min.z <= max.z);
+            min.x <= self.x && min.y <= self.y && min.z <= self.z && max.x >= self.x && max.y >= self.y && max.z >= self.z
+        }
     }
 
-    impl Add<&Vector> for Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<&Vector3<T>> for Point3<T> {
+        type Output = Point3<T>;
 
-        fn add(self, vec: &Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y, self.z + vec.z)
+        fn add(self, vec: &Vector3<T>) -> Point3<T> {
+            Point3::new(self.x + vec.x, self.y + vec.y, self.z + vec.z)
         }
     }
 
-    impl Add<&Vector> for &Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<&Vector3<T>> for &Point3<T> {
+        type Output = Point3<T>;
 
-        fn add(self, vec: &Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y, self.z + vec.z)
+        fn add(self, vec: &Vector3<T>) -> Point3<T> {
+            Point3::new(self.x + vec.x, self.y + vec.y, self.z + vec.z)
         }
     }
 
-    impl Add<Vector> for &Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<Vector3<T>> for &Point3<T> {
+        type Output = Point3<T>;
 
-        fn add(self, vec: Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y, self.z + vec.z)
+        fn add(self, vec: Vector3<T>) -> Point3<T> {
+            Point3::new(self.x + vec.x, self.y + vec.y, self.z + vec.z)
         }
     }
 
-    impl Add<Vector> for Point {
-        type Output = Point;
+    impl<T: Num + Copy> Add<Vector3<T>> for Point3<T> {
+        type Output = Point3<T>;
 
-        fn add(self, vec: Vector) -> Point {
-            point(self.x + vec.x, self.y + vec.y, self.z + vec.z)
+        fn add(self, vec: Vector3<T>) -> Point3<T> {
+            Point3::new(self.x + vec.x, self.y + vec.y, self.z + vec.z)
         }
     }
 
-    impl AddAssign<Vector> for Point {
-        fn add_assign(&mut self, vec: Vector) {
-            *self = point(self.x + vec.x, self.y + vec.y, self.z + vec.z);
+    impl<T: Num + Copy> AddAssign<Vector3<T>> for Point3<T> {
+        fn add_assign(&mut self, vec: Vector3<T>) {
+            *self = Point3::new(self.x + vec.x, self.y + vec.y, self.z + vec.z);
         }
     }
 
-    impl Sub for Point {
-        type Output = Vector;
+    impl<T: Num + Copy> Sub for Point3<T> {
+        type Output = Vector3<T>;
 
-        fn sub(self, point: Point) -> Vector { vector(self.x - point.x, self.y - point.y, self.z - point.z) }
+        fn sub(self, point: Point3<T>) -> Vector3<T> { Vector3::new(self.x - point.x, self.y - point.y, self.z - point.z) }
     }
 
-    impl Sub<&Point> for Point {
-        type Output = Vector;
+    impl<T: Num + Copy> Sub<&Point3<T>> for Point3<T> {
+        type Output = Vector3<T>;
 
-        fn sub(self, point: &Point) -> Vector { vector(self.x - point.x, self.y - point.y, self.z - point.z) }
+        fn sub(self, point: &Point3<T>) -> Vector3<T> { Vector3::new(self.x - point.x, self.y - point.y, self.z - point.z) }
     }
 
-    impl Sub for &Point {
-        type Output = Vector;
+    impl<T: Num + Copy> Sub for &Point3<T> {
+        type Output = Vector3<T>;
 
-        fn sub(self, point: &Point) -> Vector { vector(self.x - point.x, self.y - point.y, self.z - point.z) }
+        fn sub(self, point: &Point3<T>) -> Vector3<T> { Vector3::new(self.x - point.x, self.y - point.y, self.z - point.z) }
     }
 
-    impl FromStr for Point {
+    impl<T: FromStr> FromStr for Point3<T> where ParseError: From<T::Err> {
         type Err = ParseError;
 
         fn from_str(s: &str) -> Result<Self, ParseError> {
@@ -106,20 +153,20 @@ mod point {
             }
 
             let caps = regex_captures!(RE, s)?;
-            let x: i32 = capture_group!(caps, 1).trim().parse()?;
-            let y: i32 = capture_group!(caps, 2).trim().parse()?;
-            let z: i32 = capture_group!(caps, 3).trim().parse()?;
-            Ok(point(x, y, z))
+            let x: T = capture_group!(caps, 1).trim().parse()?;
+            let y: T = capture_group!(caps, 2).trim().parse()?;
+            let z: T = capture_group!(caps, 3).trim().parse()?;
+            Ok(Point3::new(x, y, z))
         }
     }
 
-    impl fmt::Debug for Point {
+    impl<T: fmt::Display> fmt::Debug for Point3<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "({}, {}, {})", self.x, self.y, self.z)
         }
     }
 
-    impl fmt::Display for Point {
+    impl<T: fmt::Display> fmt::Display for Point3<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "{:?}", self)
         }
@@ -135,6 +182,7 @@ mod point {
             assert_eq!("-3,-4,-5".parse::<Point>(), Ok(point(-3, -4, -5)));
             assert_eq!("(40,30,50)".parse::<Point>(), Ok(point(40, 30, 50)));
             assert_eq!("(-3, -5, -4)".parse::<Point>(), Ok(point(-3, -5, -4)));
+            assert_eq!("(0.5, 1, 3e9)".parse::<Point3<f64>>(), Ok(Point3::new(0.5, 1.0, 3e9)));
 
             assert!("abc".parse::<Point>().is_err());
             assert!("(1, 2)".parse::<Point>().is_err());
@@ -144,6 +192,30 @@ mod point {
         fn bounding() {
             let points = vec!(point(1, 2, 3), point(2, 3, 4), point(0, 5, 3));
             assert_eq!(Point::bounding_box(points), Some((point(0, 2, 3), point(2, 5, 4))));
+            assert_eq!(Point::bounding_box(vec!(point(1, 2, 3))), Some((point(1, 2, 3), point(1, 2, 3))));
+            assert_eq!(Point::bounding_box(vec!()), None);
+            let points = vec!(Point3::new(0.5, 2.0, -1.0), Point3::new(-1.0, 2.5, 0.0));
+            assert_eq!(Point3::bounding_box(points), Some((Point3::new(-1.0, 2.0, -1.0), Point3::new(0.5, 2.5, 0.0))));
+        }
+
+        #[test]
+        fn in_bounds_() {
+            let (min, max) = (point(0, 0, 0), point(2, 2, 2));
+            assert!(point(2, 2, 2).in_bounds(min, max));
+            assert!(point(1, 0, 2).in_bounds(min, max));
+            assert!(!point(1, 3, 1).in_bounds(min, max));
+            assert!(!point(1, 1, -1).in_bounds(min, max));
+        }
+
+        #[test]
+        fn neighbors() {
+            let p = point(1, 2, 3);
+            assert_eq!(p.neighbors6().count(), 6);
+            assert!(p.neighbors6().all(|n| (n - p).grid_len() == 1));
+            let all: std::collections::HashSet<_> = p.neighbors26().collect();
+            assert_eq!(all.len(), 26);
+            assert!(!all.contains(&p));
+            assert!(all.contains(&point(0, 1, 2)) && all.contains(&point(2, 3, 4)));
         }
 
         #[test]
@@ -156,56 +228,110 @@ mod point {
         }
     }
 }
-pub use self::point::{Point,point};
+pub use self::point::{Point,Point3,point};
 
 mod vector {
     use std::fmt;
     use std::str::FromStr;
+    use std::ops::{Add,AddAssign,Mul};
+    use num_traits::{Num, Signed, ToPrimitive};
     use crate::error::ParseError;
 
     #[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
-    pub struct Vector {
-        pub x: i32,
-        pub y: i32,
-        pub z: i32,
+    pub struct Vector3<T> {
+        pub x: T,
+        pub y: T,
+        pub z: T,
     }
 
+    pub type Vector = Vector3<i32>;
+
     #[inline]
     pub const fn vector(x: i32, y: i32, z: i32) -> Vector {
-        Vector { x, y, z }
+        Vector3 { x, y, z }
     }
 
-    impl Vector {
-        pub fn abs(&self) -> Vector {
-            vector(self.x.abs(), self.y.abs(), self.z.abs())
+    impl<T> Vector3<T> {
+        #[inline]
+        pub const fn new(x: T, y: T, z: T) -> Vector3<T> {
+            Vector3 { x, y, z }
         }
+    }
 
+    impl<T: ToPrimitive> Vector3<T> {
         pub fn len(&self) -> f64 {
-            unimplemented!()
+            let f = |c: &T| c.to_f64().expect("Representable");
+            (f(&self.x).powi(2) + f(&self.y).powi(2) + f(&self.z).powi(2)).sqrt()
         }
+    }
 
-        pub fn grid_len(&self) -> u32 {
-            (self.x.abs() + self.y.abs() + self.z.abs()) as u32
+    impl<T: Num + Copy> Vector3<T> {
+        pub fn dot(&self, other: Vector3<T>) -> T {
+            self.x * other.x + self.y * other.y + self.z * other.z
+        }
+
+        pub fn cross(&self, other: Vector3<T>) -> Vector3<T> {
+            Vector3::new(
+                self.y * other.z - self.z * other.y,
+                self.z * other.x - self.x * other.z,
+                self.x * other.y - self.y * other.x)
         }
     }
 
-    impl FromStr for Vector {
+    impl<T: Signed + Copy> Vector3<T> {
+        pub fn abs(&self) -> Vector3<T> {
+            Vector3::new(self.x.abs(), self.y.abs(), self.z.abs())
+        }
+
+        pub fn grid_len(&self) -> T {
+            self.x.abs() + self.y.abs() + self.z.abs()
+        }
+
+        // A vector of each component's sign, i.e. the direction towards this vector along each axis
+        pub fn signum(&self) -> Vector3<T> {
+            Vector3::new(self.x.signum(), self.y.signum(), self.z.signum())
+        }
+    }
+
+    impl<T: Num + Copy> Add for Vector3<T> {
+        type Output = Vector3<T>;
+
+        fn add(self, vec: Vector3<T>) -> Vector3<T> {
+            Vector3::new(self.x + vec.x, self.y + vec.y, self.z + vec.z)
+        }
+    }
+
+    impl<T: Num + Copy> AddAssign for Vector3<T> {
+        fn add_assign(&mut self, vec: Vector3<T>) {
+            *self = *self + vec;
+        }
+    }
+
+    impl<T: Num + Copy> Mul<T> for Vector3<T> {
+        type Output = Vector3<T>;
+
+        fn mul(self, m: T) -> Vector3<T> {
+            Vector3::new(self.x * m, self.y * m, self.z * m)
+        }
+    }
+
+    impl<T: FromStr> FromStr for Vector3<T> where ParseError: From<T::Err> {
         type Err = ParseError;
 
         fn from_str(s: &str) -> Result<Self, ParseError> {
             // Just reuse point's parser
-            let p: super::Point = s.parse()?;
-            Ok(vector(p.x, p.y, p.z))
+            let p = s.parse::<super::Point3<T>>()?;
+            Ok(Vector3::new(p.x, p.y, p.z))
         }
     }
 
-    impl fmt::Debug for Vector {
+    impl<T: fmt::Display> fmt::Debug for Vector3<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "({}, {}, {})", self.x, self.y, self.z)
         }
     }
 
-    impl fmt::Display for Vector {
+    impl<T: fmt::Display> fmt::Display for Vector3<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "{:?}", self)
         }
@@ -222,6 +348,29 @@ mod vector {
             assert_eq!("-3,-4,-5".parse::<Vector>(), Ok(vector(-3, -4, -5)));
         }
 
+        #[test]
+        fn len() {
+            assert_eq!(vector(2, -3, 6).len(), 7_f64);
+            assert_eq!(Vector3::new(0.0, 0.6, 0.8).len(), 1_f64);
+        }
+
+        #[test]
+        fn arithmetic() {
+            assert_eq!(vector(1, -2, 3) * 2, vector(2, -4, 6));
+            assert_eq!(vector(1, -2, 3) + vector(-1, 1, 1), vector(0, -1, 4));
+            assert_eq!(vector(5, -2, 0).signum(), vector(1, -1, 0));
+        }
+
+        #[test]
+        fn products() {
+            assert_eq!(vector(1, 2, 3).dot(vector(4, -5, 6)), 12);
+            assert_eq!(vector(1, 0, 0).cross(vector(0, 1, 0)), vector(0, 0, 1));
+            assert_eq!(vector(0, 1, 0).cross(vector(1, 0, 0)), vector(0, 0, -1));
+            let (a, b) = (vector(2, 3, 4), vector(5, 6, 7));
+            assert_eq!(a.cross(b).dot(a), 0);
+            assert_eq!(a.cross(b).dot(b), 0);
+        }
+
         parameterized_test::create!{ grid_lens, (p1, p2, d), {
             assert_eq!((p1 - p2).grid_len(), d);
             assert_eq!((p2 - p1).grid_len(), d);
@@ -235,4 +384,185 @@ mod vector {
         }
     }
 }
-pub use self::vector::{Vector,vector};
+pub use self::vector::{Vector,Vector3,vector};
+
+mod grid {
+    use super::*;
+    use std::fmt;
+    use std::ops::{Index, IndexMut};
+    use crate::euclid;
+
+    // A dense grid of cells covering the points from origin to origin+(width-1,height-1,depth-1),
+    // stored as a stack of layers (one per z coordinate) in row-major order
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    pub struct Grid3<T> {
+        origin: Point,
+        width: usize,
+        height: usize,
+        depth: usize,
+        cells: Vec<T>,
+    }
+
+    #[allow(dead_code)]
+    impl<T: Clone> Grid3<T> {
+        pub fn new(origin: Point, width: usize, height: usize, depth: usize, fill: T) -> Grid3<T> {
+            Grid3 { origin, width, height, depth, cells: vec!(fill; width * height * depth) }
+        }
+
+        // Stacks 2D layers, which must all be the same size, starting at z=0
+        pub fn from_layers(layers: &[euclid::Grid<T>]) -> Grid3<T> {
+            let (width, height) = layers.first().map(|l| (l.width(), l.height())).unwrap_or((0, 0));
+            assert!(layers.iter().all(|l| l.width() == width && l.height() == height), "Layers differ in size");
+            let origin = layers.first().map(|l| l.bounds().0).map(|p| point(p.x, p.y, 0)).unwrap_or(Point::ORIGIN);
+            let cells = layers.iter().flat_map(|l| l.iter().map(|(_, c)| c.clone())).collect();
+            Grid3 { origin, width, height, depth: layers.len(), cells }
+        }
+
+        // A copy of the layer at the given z coordinate, which is relative to the origin
+        pub fn layer(&self, z: usize) -> euclid::Grid<T> {
+            assert!(z < self.depth, "Layer {} out of bounds", z);
+            let origin = euclid::point(self.origin.x, self.origin.y);
+            let layer = &self.cells[z * self.width * self.height..(z + 1) * self.width * self.height];
+            euclid::Grid::from_fn(origin, self.width, self.height,
+                |p| layer[(p.y - origin.y) as usize * self.width + (p.x - origin.x) as usize].clone())
+        }
+    }
+
+    #[allow(dead_code)]
+    impl<T> Grid3<T> {
+        pub fn from_fn(origin: Point, width: usize, height: usize, depth: usize, mut f: impl FnMut(Point) -> T) -> Grid3<T> {
+            let cells = Grid3::<()>::offsets(width, height, depth).map(|v| origin + v).map(&mut f).collect();
+            Grid3 { origin, width, height, depth, cells }
+        }
+
+        fn offsets(width: usize, height: usize, depth: usize) -> impl Iterator<Item = Vector> {
+            (0..depth as i32).flat_map(move |z|
+                (0..height as i32).flat_map(move |y| (0..width as i32).map(move |x| vector(x, y, z))))
+        }
+
+        pub fn width(&self) -> usize { self.width }
+
+        pub fn height(&self) -> usize { self.height }
+
+        pub fn depth(&self) -> usize { self.depth }
+
+        // The inclusive minimum and maximum corners, consistent with Point::bounding_box()
+        pub fn bounds(&self) -> (Point, Point) {
+            (self.origin, self.origin + vector(self.width as i32 - 1, self.height as i32 - 1, self.depth as i32 - 1))
+        }
+
+        fn index_of(&self, p: Point) -> Option<usize> {
+            let o = p - self.origin;
+            if o.x < 0 || o.y < 0 || o.z < 0
+                || o.x as usize >= self.width || o.y as usize >= self.height || o.z as usize >= self.depth {
+                return None;
+            }
+            Some((o.z as usize * self.height + o.y as usize) * self.width + o.x as usize)
+        }
+
+        pub fn contains(&self, p: Point) -> bool {
+            self.index_of(p).is_some()
+        }
+
+        pub fn get(&self, p: Point) -> Option<&T> {
+            self.index_of(p).map(|i| &self.cells[i])
+        }
+
+        pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+            self.index_of(p).map(move |i| &mut self.cells[i])
+        }
+
+        // Every point in the grid, layer by layer in row-major order
+        pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+            Grid3::<()>::offsets(self.width, self.height, self.depth).map(move |v| self.origin + v)
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+            self.points().zip(self.cells.iter())
+        }
+
+        // The in-bounds orthogonally adjacent points
+        pub fn neighbors6(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+            p.neighbors6().filter(move |&n| self.contains(n))
+        }
+
+        // The in-bounds orthogonally and diagonally adjacent points
+        pub fn neighbors26(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+            p.neighbors26().filter(move |&n| self.contains(n))
+        }
+    }
+
+    impl<T> Index<Point> for Grid3<T> {
+        type Output = T;
+
+        fn index(&self, p: Point) -> &T {
+            self.get(p).unwrap_or_else(|| panic!("{} is outside {:?}", p, self.bounds()))
+        }
+    }
+
+    impl<T> IndexMut<Point> for Grid3<T> {
+        fn index_mut(&mut self, p: Point) -> &mut T {
+            let bounds = self.bounds();
+            self.get_mut(p).unwrap_or_else(|| panic!("{} is outside {:?}", p, bounds))
+        }
+    }
+
+    impl<T: fmt::Debug> fmt::Debug for Grid3<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Grid3 {:?}: ", self.bounds())?;
+            f.debug_list().entries(self.cells.chunks(std::cmp::max(self.width, 1))).finish()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn sample() -> Grid3<i32> {
+            Grid3::from_fn(point(-1, 0, 1), 3, 2, 4, |p| p.x + 10 * p.y + 100 * p.z)
+        }
+
+        #[test]
+        fn construction() {
+            let grid = sample();
+            assert_eq!((grid.width(), grid.height(), grid.depth()), (3, 2, 4));
+            assert_eq!(grid.bounds(), (point(-1, 0, 1), point(1, 1, 4)));
+            assert_eq!(grid.points().count(), 24);
+            assert_eq!(Point::bounding_box(grid.points()), Some(grid.bounds()));
+            assert!(grid.iter().all(|(p, &v)| v == p.x + 10 * p.y + 100 * p.z));
+        }
+
+        #[test]
+        fn access() {
+            let mut grid = sample();
+            assert_eq!(grid[point(1, 1, 4)], 411);
+            assert_eq!(grid.get(point(2, 0, 1)), None);
+            assert_eq!(grid.get(point(0, 0, 0)), None);
+            grid[point(0, 1, 2)] = -5;
+            assert_eq!(grid.get(point(0, 1, 2)), Some(&-5));
+        }
+
+        parameterized_test::create!{ neighbors, (p, six, twenty_six), {
+            let grid = Grid3::new(Point::ORIGIN, 3, 3, 3, ());
+            assert_eq!(grid.neighbors6(p).count(), six);
+            assert_eq!(grid.neighbors26(p).count(), twenty_six);
+        }}
+        neighbors! {
+            corner: (point(0, 0, 0), 3, 7),
+            edge: (point(1, 0, 0), 4, 11),
+            face: (point(1, 1, 0), 5, 17),
+            center: (point(1, 1, 1), 6, 26),
+        }
+
+        #[test]
+        fn layers() {
+            let layer = euclid::Grid::parse("#.\n.#", |c| Some(c == '#')).unwrap();
+            let grid = Grid3::from_layers(&[layer.clone(), euclid::Grid::new(euclid::Point::ORIGIN, 2, 2, false)]);
+            assert_eq!(grid.depth(), 2);
+            assert!(grid[point(1, 1, 0)]);
+            assert!(!grid[point(1, 1, 1)]);
+            assert_eq!(grid.layer(0), layer);
+        }
+    }
+}
+pub use self::grid::Grid3;
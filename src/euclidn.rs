@@ -0,0 +1,229 @@
+// Points and vectors in any number of dimensions, for spaces that don't warrant dedicated types,
+// such as a 2D map with a third "recursion depth" axis. Prefer euclid or euclid3d where they fit,
+// since named x/y/z coordinates read better than indices.
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, Mul, Sub};
+use std::str::FromStr;
+use crate::error::ParseError;
+use crate::{euclid, euclid3d};
+
+#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct PointN<const N: usize>([i32; N]);
+
+#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct VectorN<const N: usize>([i32; N]);
+
+#[allow(dead_code)]
+impl<const N: usize> PointN<N> {
+    pub const ORIGIN: PointN<N> = PointN([0; N]);
+
+    #[inline]
+    pub const fn new(coords: [i32; N]) -> PointN<N> {
+        PointN(coords)
+    }
+
+    pub fn coords(&self) -> [i32; N] { self.0 }
+
+    // The 2N orthogonally adjacent points
+    pub fn neighbors(&self) -> impl Iterator<Item = PointN<N>> {
+        let p = *self;
+        (0..N).flat_map(move |axis| [-1, 1].iter().map(move |&d| p + VectorN::unit(axis) * d))
+    }
+
+    // Every adjacent point, including diagonals; there are 3^N-1 of them
+    pub fn neighbors_all(&self) -> impl Iterator<Item = PointN<N>> {
+        let p = *self;
+        (0..3_usize.pow(N as u32))
+            .map(|mut n| {
+                let mut coords = [0; N];
+                for c in coords.iter_mut() {
+                    *c = (n % 3) as i32 - 1;
+                    n /= 3;
+                }
+                VectorN(coords)
+            })
+            .filter(|v| v != &VectorN::ZERO)
+            .map(move |v| p + v)
+    }
+}
+
+#[allow(dead_code)]
+impl<const N: usize> VectorN<N> {
+    pub const ZERO: VectorN<N> = VectorN([0; N]);
+
+    #[inline]
+    pub const fn new(coords: [i32; N]) -> VectorN<N> {
+        VectorN(coords)
+    }
+
+    // The vector of length one along the given axis
+    pub fn unit(axis: usize) -> VectorN<N> {
+        assert!(axis < N, "Axis {} out of bounds", axis);
+        let mut coords = [0; N];
+        coords[axis] = 1;
+        VectorN(coords)
+    }
+
+    pub fn coords(&self) -> [i32; N] { self.0 }
+
+    pub fn abs(&self) -> VectorN<N> {
+        VectorN(self.0.map(i32::abs))
+    }
+
+    pub fn grid_len(&self) -> i32 {
+        self.0.iter().map(|c| c.abs()).sum()
+    }
+}
+
+impl<const N: usize> Index<usize> for PointN<N> {
+    type Output = i32;
+
+    fn index(&self, axis: usize) -> &i32 { &self.0[axis] }
+}
+
+impl<const N: usize> Index<usize> for VectorN<N> {
+    type Output = i32;
+
+    fn index(&self, axis: usize) -> &i32 { &self.0[axis] }
+}
+
+impl<const N: usize> Add<VectorN<N>> for PointN<N> {
+    type Output = PointN<N>;
+
+    fn add(mut self, vec: VectorN<N>) -> PointN<N> {
+        self.0.iter_mut().zip(vec.0.iter()).for_each(|(c, v)| *c += v);
+        self
+    }
+}
+
+impl<const N: usize> AddAssign<VectorN<N>> for PointN<N> {
+    fn add_assign(&mut self, vec: VectorN<N>) {
+        *self = *self + vec;
+    }
+}
+
+impl<const N: usize> Sub for PointN<N> {
+    type Output = VectorN<N>;
+
+    fn sub(self, point: PointN<N>) -> VectorN<N> {
+        let mut coords = self.0;
+        coords.iter_mut().zip(point.0.iter()).for_each(|(c, p)| *c -= p);
+        VectorN(coords)
+    }
+}
+
+impl<const N: usize> Mul<i32> for VectorN<N> {
+    type Output = VectorN<N>;
+
+    fn mul(self, m: i32) -> VectorN<N> {
+        VectorN(self.0.map(|c| c * m))
+    }
+}
+
+impl From<euclid::Point> for PointN<2> {
+    fn from(p: euclid::Point) -> PointN<2> { PointN([p.x, p.y]) }
+}
+
+impl From<PointN<2>> for euclid::Point {
+    fn from(p: PointN<2>) -> euclid::Point { euclid::point(p[0], p[1]) }
+}
+
+impl From<euclid3d::Point> for PointN<3> {
+    fn from(p: euclid3d::Point) -> PointN<3> { PointN([p.x, p.y, p.z]) }
+}
+
+// A 2D point on a given layer
+impl From<(euclid::Point, i32)> for PointN<3> {
+    fn from((p, z): (euclid::Point, i32)) -> PointN<3> { PointN([p.x, p.y, z]) }
+}
+
+impl<const N: usize> FromStr for PointN<N> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let trimmed = s.trim().trim_start_matches('(').trim_end_matches(')');
+        let parts: Vec<_> = trimmed.split(',').collect();
+        if parts.len() != N {
+            return Err(format!("point, expected {} coordinates: {}", N, s).into());
+        }
+        let mut coords = [0; N];
+        for (c, part) in coords.iter_mut().zip(parts) {
+            *c = part.trim().parse()?;
+        }
+        Ok(PointN(coords))
+    }
+}
+
+fn fmt_coords(coords: &[i32], f: &mut fmt::Formatter) -> fmt::Result {
+    let coords: Vec<_> = coords.iter().map(|c| c.to_string()).collect();
+    write!(f, "({})", coords.join(", "))
+}
+
+impl<const N: usize> fmt::Debug for PointN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt_coords(&self.0, f) }
+}
+
+impl<const N: usize> fmt::Display for PointN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+}
+
+impl<const N: usize> fmt::Debug for VectorN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt_coords(&self.0, f) }
+}
+
+impl<const N: usize> fmt::Display for VectorN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn parse() {
+        assert_eq!("(1, -2, 3, 4)".parse::<PointN<4>>(), Ok(PointN::new([1, -2, 3, 4])));
+        assert_eq!("5".parse::<PointN<1>>(), Ok(PointN::new([5])));
+        assert!("1, 2".parse::<PointN<3>>().is_err());
+        assert!("1, a".parse::<PointN<2>>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(PointN::new([1, -2, 3, 4]).to_string(), "(1, -2, 3, 4)");
+        assert_eq!(format!("{:?}", VectorN::new([0, 1])), "(0, 1)");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let p = PointN::new([1, 2, 3, 4]);
+        let v = VectorN::new([-1, 0, 2, 5]);
+        assert_eq!(p + v, PointN::new([0, 2, 5, 9]));
+        assert_eq!((p + v) - p, v);
+        assert_eq!(v * -2, VectorN::new([2, 0, -4, -10]));
+        assert_eq!((p - PointN::ORIGIN).grid_len(), 10);
+        assert_eq!(v.abs().grid_len(), v.grid_len());
+    }
+
+    parameterized_test::create!{ neighbors, (p, orthogonal, all), {
+        let p: PointN<4> = p;
+        let orthogonal_set: HashSet<_> = p.neighbors().collect();
+        let all_set: HashSet<_> = p.neighbors_all().collect();
+        assert_eq!((orthogonal_set.len(), all_set.len()), (orthogonal, all));
+        assert!(orthogonal_set.iter().all(|&n| (n - p).grid_len() == 1));
+        assert!(orthogonal_set.is_subset(&all_set));
+        assert!(!all_set.contains(&p));
+    }}
+    neighbors! {
+        origin: (PointN::ORIGIN, 8, 80),
+        elsewhere: (PointN::new([3, -1, 0, 7]), 8, 80),
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(PointN::from(euclid::point(1, 2)), PointN::new([1, 2]));
+        assert_eq!(euclid::Point::from(PointN::new([1, 2])), euclid::point(1, 2));
+        assert_eq!(PointN::from(euclid3d::point(1, 2, 3)), PointN::new([1, 2, 3]));
+        assert_eq!(PointN::from((euclid::point(1, 2), -3)), PointN::new([1, 2, -3]));
+    }
+}
@@ -0,0 +1,220 @@
+// A minimal animated GIF encoder, see https://www.w3.org/Graphics/GIF/spec-gif89a.txt and
+// http://giflib.sourceforge.net/whatsinagif/ for an approachable walkthrough of the format.
+//
+// Every frame covers the full image and uses the single global color table; there's no support for
+// transparency, local color tables, or interlacing.
+
+pub struct Frame {
+    // One color table index per pixel, in row-major order
+    pub pixels: Vec<u8>,
+    // In hundredths of a second
+    pub delay: u16,
+}
+
+pub fn encode(width: u16, height: u16, colors: &[[u8; 3]], frames: &[Frame]) -> Vec<u8> {
+    assert!(!colors.is_empty() && colors.len() <= 256, "Invalid color table size: {}", colors.len());
+    // The table must have 2^(n+1) entries; it also determines the minimum LZW code size, which
+    // cannot be less than 2
+    let table_bits = (1..=8).find(|b| 1 << b >= colors.len()).expect("At most 256 colors");
+    let min_code_size = std::cmp::max(2, table_bits);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"GIF89a");
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    out.push(0b1111_0000 | (table_bits - 1)); // global color table, 8-bit color resolution
+    out.push(0); // background color index
+    out.push(0); // pixel aspect ratio
+    for i in 0..1 << table_bits {
+        out.extend_from_slice(colors.get(i).unwrap_or(&[0, 0, 0]));
+    }
+
+    // Netscape application extension, to loop forever
+    out.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    out.extend_from_slice(b"NETSCAPE2.0");
+    out.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        assert_eq!(frame.pixels.len(), width as usize * height as usize);
+        debug_assert!(frame.pixels.iter().all(|&p| (p as usize) < colors.len()));
+        // Graphic control extension, for the frame delay
+        out.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+        out.extend_from_slice(&frame.delay.to_le_bytes());
+        out.extend_from_slice(&[0x00, 0x00]);
+        // Image descriptor
+        out.push(0x2C);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.push(0);
+
+        out.push(min_code_size);
+        for block in lzw(min_code_size, &frame.pixels).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0);
+    }
+
+    out.push(0x3B);
+    out
+}
+
+const MAX_CODE: u16 = 4096;
+
+// GIF's variable-width LZW compression; codes grow from min_code_size+1 bits up to 12 bits, at
+// which point the table is reset with a clear code.
+fn lzw(min_code_size: u8, pixels: &[u8]) -> Vec<u8> {
+    use std::collections::HashMap;
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut out = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    out.write(clear, code_size);
+    let mut pixels = pixels.iter();
+    let mut prefix = match pixels.next() {
+        Some(&p) => p as u16,
+        None => { out.write(end, code_size); return out.finish(); },
+    };
+    for &pixel in pixels {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, code_size);
+        // The decoder adds table entries one code behind the encoder, so the code size grows once
+        // the table entries written so far no longer fit
+        if next == 1 << code_size && code_size < 12 {
+            code_size += 1;
+        }
+        if next < MAX_CODE {
+            table.insert((prefix, pixel), next);
+            next += 1;
+        } else {
+            out.write(clear, code_size);
+            table.clear();
+            next = end + 1;
+            code_size = min_code_size + 1;
+        }
+        prefix = pixel as u16;
+    }
+    out.write(prefix, code_size);
+    if next == 1 << code_size && code_size < 12 {
+        code_size += 1;
+    }
+    out.write(end, code_size);
+    out.finish()
+}
+
+// Packs codes least-significant bit first, as GIF expects
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        debug_assert!(code < 1 << size, "{} doesn't fit in {} bits", code, size);
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A straightforward decoder, to check the encoder against
+    fn unlzw(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let end = clear + 1;
+        let reset = || -> Vec<Vec<u8>> {
+            let mut table: Vec<_> = (0..clear).map(|c| vec!(c as u8)).collect();
+            table.push(vec!());
+            table.push(vec!());
+            table
+        };
+
+        let mut table = reset();
+        let mut code_size = min_code_size + 1;
+        let mut prior: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        let (mut buffer, mut bits, mut bytes) = (0u32, 0u8, data.iter());
+        loop {
+            while bits < code_size {
+                buffer |= (*bytes.next().expect("Missing end code") as u32) << bits;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << code_size) - 1)) as u16;
+            buffer >>= code_size;
+            bits -= code_size;
+
+            if code == clear {
+                table = reset();
+                code_size = min_code_size + 1;
+                prior = None;
+                continue;
+            }
+            if code == end { return out; }
+            let entry = match (table.get(code as usize), &prior) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prior)) => { let mut e = prior.clone(); e.push(prior[0]); e },
+                (None, None) => panic!("Invalid code {}", code),
+            };
+            out.extend_from_slice(&entry);
+            if let Some(prior) = prior {
+                if table.len() < MAX_CODE as usize {
+                    let mut e = prior.clone();
+                    e.push(entry[0]);
+                    table.push(e);
+                }
+            }
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+            prior = Some(entry);
+        }
+    }
+
+    parameterized_test::create!{ round_trip, (min_code_size, pixels), {
+        let pixels: Vec<u8> = pixels;
+        assert_eq!(unlzw(min_code_size, &lzw(min_code_size, &pixels)), pixels);
+    }}
+    round_trip! {
+        empty: (2, vec!()),
+        one: (2, vec!(3)),
+        repeated: (2, vec!(1; 1000)),
+        stripes: (2, (0..1000).map(|i| (i / 7 % 3) as u8).collect()),
+        noisy: (3, (0..20000u32).map(|i| ((i * 7919 + i / 13) % 8) as u8).collect()),
+    }
+
+    #[test]
+    fn structure() {
+        let colors = [[0, 0, 0], [255, 255, 255], [255, 0, 0]];
+        let frames = [Frame { pixels: vec!(0, 1, 2, 1), delay: 10 }, Frame { pixels: vec!(2, 2, 2, 2), delay: 20 }];
+        let gif = encode(2, 2, &colors, &frames);
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(&gif[6..10], &[2, 0, 2, 0]);
+        assert_eq!(gif[10], 0b1111_0001); // 4 color table
+        assert_eq!(&gif[13..25], &[0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 0]);
+        assert_eq!(gif[gif.len() - 1], 0x3B);
+    }
+}
@@ -0,0 +1,97 @@
+// Resolves puzzle inputs by name (e.g. "day1.txt") from a configurable source, rather than every
+// module opening relative paths itself. By default inputs are read from the directory named by
+// $ADVENT_DATA_DIR, falling back to ./data and then to the data/ directory of this crate.
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use crate::error::InputError;
+
+pub const DATA_DIR_VAR: &str = "ADVENT_DATA_DIR";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+    // Inputs are files inside this directory
+    Directory(PathBuf),
+    // Every input is read from stdin; only sensible when running a single day
+    Stdin,
+    // Every input is this string; useful for tests and embedding
+    #[allow(dead_code)]
+    Memory(String),
+}
+
+impl Source {
+    pub fn from_env() -> Source {
+        match env::var_os(DATA_DIR_VAR) {
+            Some(dir) => Source::Directory(dir.into()),
+            None => Source::Directory(default_dir()),
+        }
+    }
+
+    pub fn read(&self, name: &str) -> Result<String, InputError> {
+        match self {
+            Source::Directory(dir) => {
+                let path = dir.join(name);
+                fs::read_to_string(&path).map_err(|e| InputError::new(path.display(), e))
+            },
+            Source::Stdin => read_stdin(),
+            Source::Memory(input) => Ok(input.clone()),
+        }
+    }
+}
+
+fn default_dir() -> PathBuf {
+    let local = Path::new("data");
+    if local.is_dir() {
+        return local.into();
+    }
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+}
+
+// stdin can only be consumed once, so cache it for callers that read their input repeatedly
+fn read_stdin() -> Result<String, InputError> {
+    lazy_static! {
+        static ref STDIN: Result<String, String> = {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input).map_err(|e| e.to_string())
+        };
+    }
+    STDIN.clone().map_err(|e| InputError::new("<stdin>", io::Error::other(e)))
+}
+
+lazy_static! {
+    static ref SOURCE: RwLock<Source> = RwLock::new(Source::from_env());
+}
+
+pub fn set_source(source: Source) {
+    *SOURCE.write().expect("Poisoned") = source;
+}
+
+pub fn try_read(name: &str) -> Result<String, InputError> {
+    SOURCE.read().expect("Poisoned").read(name)
+}
+
+pub fn read(name: &str) -> String {
+    try_read(name).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory() {
+        let source = Source::Directory(default_dir());
+        assert!(source.read("day1.txt").expect("Should exist").lines().count() > 0);
+
+        let err = source.read("day99.txt").unwrap_err().to_string();
+        assert!(err.contains("day99.txt"), "{}", err);
+    }
+
+    #[test]
+    fn memory() {
+        let source = Source::Memory("1,2,3".into());
+        assert_eq!(source.read("day2.txt").unwrap(), "1,2,3");
+    }
+}
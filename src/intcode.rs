@@ -121,10 +121,13 @@ impl Machine {
         }
     }
 
-    pub fn from_file(file: &str) -> Machine {
-        std::fs::read_to_string(file).expect("Cannot open")
-            .trim().parse().expect("Invalid program")
+    #[cfg(test)]
+    pub fn from_input(name: &str) -> Machine {
+        Machine::from_program(&crate::input::read(name))
+    }
 
+    pub fn from_program(program: &str) -> Machine {
+        program.trim().parse().expect("Invalid program")
     }
 
     pub fn send_input(&mut self, input: i64) {
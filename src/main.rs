@@ -4,6 +4,7 @@
 extern crate parameterized_test;
 extern crate mod_exp;
 extern crate num_integer;
+extern crate num_traits;
 extern crate permutohedron;
 extern crate regex;
 
@@ -25,12 +26,20 @@ macro_rules! interactive {
     };
 }
 
+mod bench;
 mod console;
 mod error;
 mod euclid;
 mod euclid3d;
+mod euclidn;
+mod gif;
+mod input;
 mod intcode;
 mod pathfinding;
+mod puzzle;
+mod recorder;
+mod report;
+mod verify;
 
 mod aoc1;
 mod aoc2;
@@ -58,44 +67,158 @@ mod aoc23;
 mod aoc24;
 mod aoc25;
 
+fn puzzles() -> Vec<puzzle::Puzzle> {
+    vec!(
+        aoc1::puzzle(), aoc2::puzzle(), aoc3::puzzle(), aoc4::puzzle(), aoc5::puzzle(),
+        aoc6::puzzle(), aoc7::puzzle(), aoc8::puzzle(), aoc9::puzzle(), aoc10::puzzle(),
+        aoc11::puzzle(), aoc12::puzzle(), aoc13::puzzle(), aoc14::puzzle(), aoc15::puzzle(),
+        aoc16::puzzle(), aoc17::puzzle(), aoc18::puzzle(), aoc19::puzzle(), aoc20::puzzle(),
+        aoc21::puzzle(), aoc22::puzzle(), aoc23::puzzle(), aoc24::puzzle(), aoc25::puzzle(),
+    )
+}
+
 fn main() {
-    let _console = console::Console::init();
-    println!(); // split build output from runtime output
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    match take_input_source(&mut args) {
+        Ok(Some(source)) => input::set_source(source),
+        Ok(None) => {},
+        Err(e) => {
+            eprintln!("{}", e);
+            ::std::process::exit(1);
+        }
+    }
     if args.len() < 2 {
-        println!("Usage: {} DAY_OF_ADVENT", args[0]);
+        println!("Usage: {} [--data-dir DIR | --stdin] [--format text|json|csv] [--capture DIR [--capture-format cast|gif]] DAY_OF_ADVENT...", args[0]);
+        println!("       {} [--data-dir DIR] bench [--iterations N] [--save FILE] [--baseline FILE] [--threshold PERCENT] [DAY...]", args[0]);
+        println!("       {} [--data-dir DIR] verify [--record FILE] [DAY...]", args[0]);
+        println!("  Inputs are read from ${} if set, otherwise ./data", input::DATA_DIR_VAR);
         return;
     }
-    let day: u32 = args[1].parse().expect("Should be a natural number");
-    match day {
-        1 => aoc1::advent(),
-        2 => aoc2::advent(),
-        3 => aoc3::advent(),
-        4 => aoc4::advent(),
-        5 => aoc5::advent(),
-        6 => aoc6::advent(),
-        7 => aoc7::advent(),
-        8 => aoc8::advent(),
-        9 => aoc9::advent(),
-        10 => aoc10::advent(),
-        11 => aoc11::advent(),
-        12 => aoc12::advent(),
-        13 => aoc13::advent(),
-        14 => aoc14::advent(),
-        15 => aoc15::advent(),
-        16 => aoc16::advent(),
-        17 => aoc17::advent(),
-        18 => aoc18::advent(),
-        19 => aoc19::advent(),
-        20 => aoc20::advent(),
-        21 => aoc21::advent(),
-        22 => aoc22::advent(),
-        23 => aoc23::advent(),
-        24 => aoc24::advent(),
-        25 => aoc25::advent(),
-        x => {
-            eprintln!("Day {} hasn't happened yet.", x);
-            ::std::process::exit(1);
-        },
+    let result = match args[1].as_str() {
+        "bench" => bench::main(&puzzles(), &args[2..]),
+        "verify" => verify::main(&puzzles(), &args[2..]),
+        _ => run(&args[1..]).map(|report| print!("{}", report)),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        ::std::process::exit(1);
+    }
+}
+
+// Removes the --data-dir DIR or --stdin flag from wherever it appears in the args, since it
+// applies to every subcommand
+fn take_input_source(args: &mut Vec<String>) -> Result<Option<input::Source>, String> {
+    let mut source = None;
+    let mut i = 0;
+    while i < args.len() {
+        let found = match args[i].as_str() {
+            "--data-dir" => {
+                args.remove(i);
+                if i == args.len() { return Err("--data-dir requires a value".into()); }
+                input::Source::Directory(args.remove(i).into())
+            },
+            "--stdin" => {
+                args.remove(i);
+                input::Source::Stdin
+            },
+            _ => { i += 1; continue; },
+        };
+        if source.replace(found).is_some() {
+            return Err("Only one of --data-dir or --stdin can be given".into());
+        }
+    }
+    Ok(source)
+}
+
+// Solves the given days, returning the report to print
+fn run(args: &[String]) -> Result<String, String> {
+    let mut format = report::Format::Text;
+    let mut capture_dir = None;
+    let mut capture_format = recorder::Format::Cast;
+    let mut days = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().ok_or("--format requires a value")?.parse()?,
+            "--capture" => capture_dir = Some(args.next().ok_or("--capture requires a value")?.clone()),
+            "--capture-format" => capture_format = args.next().ok_or("--capture-format requires a value")?.parse()?,
+            day => days.push(day.parse::<u32>().map_err(|_| format!("Should be a natural number: {}", day))?),
+        }
+    }
+    if let Some(dir) = capture_dir {
+        recorder::set_capture(dir, capture_format);
+    }
+    // Structured output shouldn't be preceded or interrupted by anything else on stdout, but in
+    // text mode a blank line separates build output from runtime output
+    if format == report::Format::Text {
+        println!();
+    } else if console::interactive() {
+        console::set_output(console::Output::Stderr);
+    }
+    let _console = console::Console::init();
+
+    let puzzles = puzzles();
+    let mut results = Vec::new();
+    for day in days {
+        let puzzle = puzzles.iter().find(|p| p.day() == day)
+            .ok_or_else(|| format!("Day {} hasn't happened yet.", day))?;
+        let input = input::read(&puzzle.input_name());
+        // Interacting would get in the way of structured output
+        if format == report::Format::Text && console::interactive() && puzzle.interact(&input) { continue; }
+        let solution = puzzle.solve(&input);
+        if console::interactive() {
+            for note in solution.parts.iter().flat_map(|(answer, _)| answer.notes()) {
+                eprintln!("{}", note);
+            }
+        }
+        results.push((day, solution));
+    }
+    Ok(report::format(format, &results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    parameterized_test::create!{ input_source, (before, expected_source, after), {
+        let mut before = args(before);
+        assert_eq!(take_input_source(&mut before), Ok(expected_source));
+        assert_eq!(before, args(after));
+    }}
+    input_source! {
+        none: (&["advent", "1"], None, &["advent", "1"]),
+        data_dir: (&["advent", "--data-dir", "in", "1"], Some(input::Source::Directory("in".into())), &["advent", "1"]),
+        stdin_after_day: (&["advent", "1", "--stdin"], Some(input::Source::Stdin), &["advent", "1"]),
+        subcommand: (&["advent", "verify", "--data-dir", "in", "2"],
+                     Some(input::Source::Directory("in".into())), &["advent", "verify", "2"]),
+    }
+
+    parameterized_test::create!{ bad_input_source, before, {
+        assert!(take_input_source(&mut args(before)).is_err());
+    }}
+    bad_input_source! {
+        missing_dir: &["advent", "1", "--data-dir"],
+        both: &["advent", "--stdin", "1", "--data-dir", "in"],
+    }
+
+    #[test]
+    fn structured_output() {
+        // Day 13 renders the game as it's played
+        console::set_output(console::Output::Stdout);
+        let report = run(&args(&["--format", "json", "13"])).unwrap();
+        assert_eq!(console::output(), console::Output::Stderr);
+        assert_eq!(console::take_stdout(), "");
+
+        let records: Vec<_> = report.strip_prefix("[\n").and_then(|r| r.strip_suffix("\n]\n"))
+            .expect("Should be a JSON array").split(",\n").collect();
+        assert_eq!(records.len(), 2);
+        for (record, answer) in records.iter().zip(&["\"answer\": \"230\"", "\"answer\": \"11140\""]) {
+            assert!(record.starts_with("  {\"day\": 13, ") && record.ends_with('}'), "{}", record);
+            assert!(record.contains(answer), "{}", record);
+        }
     }
 }
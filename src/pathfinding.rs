@@ -1,285 +1,2104 @@
 mod internals {
-    use std::collections::{VecDeque, HashMap, BinaryHeap};
+    use std::collections::{VecDeque, HashMap, HashSet, BinaryHeap};
     use std::cmp::Ordering;
+    use std::hash::Hash;
 
     // References:
     // https://www.redblobgames.com/pathfinding/a-star/introduction.html
     // http://theory.stanford.edu/~amitp/GameProgramming/AStarComparison.html
     // https://doc.rust-lang.org/std/collections/binary_heap/
     pub trait Graph {
-        type Node: Clone + std::fmt::Debug + Eq + core::hash::Hash;
+        type Node: Clone + std::fmt::Debug + Eq + Hash;
+        type Weight: Weight;
 
-        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>>;
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>>;
 
-        fn bfs_all(&self, start: &Self::Node) -> HashMap<Self::Node, Vec<Self::Node>> {
+        #[allow(dead_code)]
+        fn bfs_all(&self, start: &Self::Node) -> SearchResult<Self::Node, Self::Weight> {
+            self.bfs(start, |_| false)
+        }
+
+        // Explores nodes in the order they're discovered, which only finds the cheapest routes if
+        // every edge has the same weight (typically 1).
+        #[allow(dead_code)]
+        fn bfs(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> SearchResult<Self::Node, Self::Weight> {
+            self.bfs_observed(start, goal_predicate, &mut ())
+        }
+
+        #[allow(dead_code)]
+        fn bfs_observed(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                        observer: &mut impl SearchObserver<Self::Node, Self::Weight>) -> SearchResult<Self::Node, Self::Weight> {
             let mut frontier = VecDeque::new();
-            frontier.push_back(start.clone());
-            let mut routes = HashMap::new();
-            routes.insert(start.clone(), start.clone()); // careful, potential infinite loop
+            frontier.push_back((start.clone(), 0));
+            let mut result: SearchResult<_, Self::Weight> = SearchResult::new(start.clone());
+            let mut step = None;
+            observer.discovered(start);
 
-            while ! frontier.is_empty() {
-                let current = frontier.pop_front().expect("frontier is not empty");
+            while let Some((current, depth)) = frontier.pop_front() {
+                let cost = result.reached[&current].0;
+                match observer.check(&current, cost, depth) {
+                    Control::Continue => {},
+                    Control::Skip(reason) => { result.stop_reason = reason; continue; },
+                    Control::Stop(reason) => { result.stop_reason = reason; break; },
+                }
+                if goal_predicate(&current) {
+                    result.set_goal(current);
+                    break;
+                }
+                observer.expanded(&current, frontier.len());
                 for edge in self.neighbors(&current) {
-                    assert!(edge.weight() == 1, "BFS does not support weighted edges: {:?}", edge);
+                    let weight = *step.get_or_insert(edge.weight());
+                    assert!(edge.weight() == weight, "BFS does not support non-uniform edge weights: {:?}", edge);
                     let next = edge.dest();
-                    if !routes.contains_key(next) {
-                        frontier.push_back(next.clone());
-                        routes.insert(next.clone(), current.clone());
+                    if !result.reached.contains_key(next) {
+                        frontier.push_back((next.clone(), depth + 1));
+                        result.reached.insert(next.clone(), (cost.plus(weight), Some(current.clone())));
+                        observer.discovered(next);
+                    } else {
+                        observer.revisited(next);
                     }
                 }
             }
+            observer.finished(&result);
+            result
+        }
+
+        // A BFS for graphs whose edges have one of two weights, zero or some other value (typically
+        // 1). Zero-weight edges are explored before any others, using a deque in place of the
+        // priority queue Dijkstra's algorithm would need.
+        #[allow(dead_code)]
+        fn bfs_01(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> SearchResult<Self::Node, Self::Weight> {
+            self.bfs_01_observed(start, goal_predicate, &mut ())
+        }
 
-            let mut paths = HashMap::new();
-            'outer: for goal in routes.keys() {
-                let mut path = Vec::new();
-                let mut current = goal.clone();
-                while current != *start {
-                    if let Some(next) = routes.get(&current) {
-                        path.push(current);
-                        current = next.clone();
+        fn bfs_01_observed(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                           observer: &mut impl SearchObserver<Self::Node, Self::Weight>) -> SearchResult<Self::Node, Self::Weight> {
+            let mut frontier = VecDeque::new();
+            frontier.push_back((Self::Weight::ZERO, 0, start.clone()));
+            let mut result = SearchResult::new(start.clone());
+            let mut step = None;
+            observer.discovered(start);
+
+            while let Some((cost, depth, current)) = frontier.pop_front() {
+                // Skip nodes that were re-queued with a lower cost after this entry was added
+                if result.cost(&current).is_some_and(|c| c < cost) { continue; }
+                match observer.check(&current, cost, depth) {
+                    Control::Continue => {},
+                    Control::Skip(reason) => { result.stop_reason = reason; continue; },
+                    Control::Stop(reason) => { result.stop_reason = reason; break; },
+                }
+                if goal_predicate(&current) {
+                    result.set_goal(current);
+                    break;
+                }
+                observer.expanded(&current, frontier.len());
+                for edge in self.neighbors(&current) {
+                    let weight = edge.weight();
+                    if weight != Self::Weight::ZERO {
+                        let step = *step.get_or_insert(weight);
+                        assert!(weight == step, "0-1 BFS does not support more than one non-zero edge weight: {:?}", edge);
+                    }
+                    let next = edge.dest();
+                    let next_cost = cost.plus(weight);
+                    if result.cost(next).is_none_or(|c| c > next_cost) {
+                        result.reached.insert(next.clone(), (next_cost, Some(current.clone())));
+                        if weight == Self::Weight::ZERO {
+                            frontier.push_front((next_cost, depth + 1, next.clone()));
+                        } else {
+                            frontier.push_back((next_cost, depth + 1, next.clone()));
+                        }
+                        observer.discovered(next);
                     } else {
-                        continue 'outer;
+                        observer.revisited(next);
                     }
                 }
-                path.push(start.clone());
-                path.reverse();
-                paths.insert(goal.clone(), path);
             }
-            paths
+            observer.finished(&result);
+            result
         }
 
-        fn bfs(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<Vec<Self::Node>> {
-            let mut frontier = VecDeque::new();
-            frontier.push_back(start.clone());
-            let mut routes = HashMap::new();
-            let mut goal = None;
+        fn dijkstras(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> SearchResult<Self::Node, Self::Weight> {
+            self.astar(start, goal_predicate, |_| Self::Weight::ZERO)
+        }
+
+        fn dijkstras_observed(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool,
+                              observer: &mut impl SearchObserver<Self::Node, Self::Weight>) -> SearchResult<Self::Node, Self::Weight> {
+            self.astar_observed(start, goal_predicate, |_| Self::Weight::ZERO, observer)
+        }
+
+        // Like dijkstras(), but explores nodes in order of their cost plus the heuristic's estimate
+        // of the remaining cost to a goal. The heuristic must be admissible, i.e. never overestimate
+        // the remaining cost, or the route found may not be the shortest.
+        fn astar(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool,
+                 heuristic: impl FnMut(&Self::Node) -> Self::Weight) -> SearchResult<Self::Node, Self::Weight> {
+            self.astar_observed(start, goal_predicate, heuristic, &mut ())
+        }
+
+        fn astar_observed(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                          mut heuristic: impl FnMut(&Self::Node) -> Self::Weight,
+                          observer: &mut impl SearchObserver<Self::Node, Self::Weight>) -> SearchResult<Self::Node, Self::Weight> {
+            let mut frontier = BinaryHeap::new();
+            let mut result = SearchResult::new(start.clone());
+            // Frontier nodes are paired with their depth
+            frontier.push(State { priority: heuristic(start), cost: Self::Weight::ZERO, node: (start.clone(), 0) });
+            observer.discovered(start);
 
-            while let Some(current) = frontier.pop_front() {
+            while let Some(State { cost, node: (current, depth), .. }) = frontier.pop() {
+                // Skip nodes that were re-queued with a lower cost after this entry was added
+                if result.cost(&current).is_some_and(|c| c < cost) { continue; }
+                match observer.check(&current, cost, depth) {
+                    Control::Continue => {},
+                    Control::Skip(reason) => { result.stop_reason = reason; continue; },
+                    Control::Stop(reason) => { result.stop_reason = reason; break; },
+                }
                 if goal_predicate(&current) {
-                    goal = Some(current.clone());
+                    result.set_goal(current);
                     break;
                 }
+                observer.expanded(&current, frontier.len());
                 for edge in self.neighbors(&current) {
-                    assert!(edge.weight() == 1, "BFS does not support weighted edges: {:?}", edge);
                     let next = edge.dest();
-                    if !routes.contains_key(next) {
-                        frontier.push_back(next.clone());
-                        routes.insert(next.clone(), current.clone());
+                    let next_cost = cost.plus(edge.weight());
+
+                    let prior_next_cost = result.cost(next);
+                    if prior_next_cost.is_none() || prior_next_cost.expect("Not-none") > next_cost {
+                        result.reached.insert(next.clone(), (next_cost, Some(current.clone())));
+                        frontier.push(State { priority: next_cost.plus(heuristic(next)), cost: next_cost, node: (next.clone(), depth + 1) });
+                        observer.discovered(next);
+                    } else {
+                        observer.revisited(next);
                     }
                 }
             }
+            observer.finished(&result);
+            result
+        }
 
-            let mut current = goal?;  // return if no goal
-            let mut path = Vec::new();
-            while current != *start {
-                if let Some(next) = routes.get(&current) {
-                    path.push(current);
-                    current = next.clone();
-                } else {
-                    unreachable!();
+        #[allow(dead_code)]
+        fn dijkstras_all(&self, start: &Self::Node) -> SearchResult<Self::Node, Self::Weight> {
+            self.dijkstras(start, |_| false)
+        }
+
+        // A level-synchronous BFS: each layer of the frontier is expanded across the given number of
+        // threads, and the results merged in order, so the result is the same as bfs()'s.
+        #[allow(dead_code)]
+        fn parallel_bfs(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                        threads: usize) -> SearchResult<Self::Node, Self::Weight>
+                where Self: Sync, Self::Node: Send + Sync, Self::Weight: Send {
+            let mut result: SearchResult<_, Self::Weight> = SearchResult::new(start.clone());
+            let mut step = None;
+            let mut layer = vec!(start.clone());
+
+            while !layer.is_empty() {
+                // bfs() stops as soon as it reaches a goal, without expanding the rest of its layer
+                let goal = layer.iter().position(&mut goal_predicate);
+                let expanding = &layer[..goal.unwrap_or(layer.len())];
+                let mut next_layer = Vec::new();
+                for (current, edges) in expanding.iter().zip(expand_all(self, expanding, threads)) {
+                    let cost = result.reached[current].0;
+                    for edge in edges {
+                        let weight = *step.get_or_insert(edge.weight());
+                        assert!(edge.weight() == weight, "BFS does not support non-uniform edge weights: {:?}", edge);
+                        let next = edge.dest();
+                        if !result.reached.contains_key(next) {
+                            result.reached.insert(next.clone(), (cost.plus(weight), Some(current.clone())));
+                            next_layer.push(next.clone());
+                        }
+                    }
+                }
+                if let Some(goal) = goal {
+                    result.set_goal(layer.swap_remove(goal));
+                    break;
                 }
+                layer = next_layer;
             }
-            path.push(start.clone());
-            path.reverse();
-            Some(path)
+            result
         }
 
-        fn dijkstras(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<Vec<Edge<Self::Node>>> {
+        // Delta-stepping, a parallel variant of Dijkstra's algorithm. Rather than expanding the
+        // cheapest node one at a time, every node within delta of the cheapest is expanded at once,
+        // across the given number of threads, until none remain. Nodes reached more cheaply within
+        // that bucket are expanded again, so a larger delta exposes more parallelism but wastes
+        // more work; with a delta of zero only the cheapest nodes are expanded together. The goal,
+        // and the cost of every node cheaper than it, are the same as dijkstras()'s.
+        fn parallel_dijkstras(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                              delta: Self::Weight, threads: usize) -> SearchResult<Self::Node, Self::Weight>
+                where Self: Sync, Self::Node: Send + Sync, Self::Weight: Send {
             let mut frontier = BinaryHeap::new();
-            let mut costs = HashMap::new();
-            let mut routes = HashMap::new();
-            let mut goal = None;
-            frontier.push(State { cost: 0, node: start.clone() });
-            costs.insert(start.clone(), 0);
-
-            while let Some(current) = frontier.pop() {
-                if goal_predicate(&current.node) {
-                    goal = Some(current.node.clone());
+            let mut result = SearchResult::new(start.clone());
+            frontier.push(State { priority: Self::Weight::ZERO, cost: Self::Weight::ZERO, node: start.clone() });
+
+            while let Some(lowest) = frontier.peek().map(|s| s.cost) {
+                let limit = lowest.plus(delta);
+                // Goals aren't expanded, and aren't settled until the whole bucket is
+                let mut goals = Vec::new();
+                loop {
+                    let mut batch = Vec::new();
+                    while frontier.peek().is_some_and(|s| s.cost <= limit) {
+                        let State { cost, node, .. } = frontier.pop().expect("Non-empty");
+                        // Skip nodes that were re-queued with a lower cost after this entry was added
+                        if result.cost(&node).is_some_and(|c| c < cost) { continue; }
+                        if goal_predicate(&node) {
+                            goals.push(node);
+                        } else {
+                            batch.push((node, cost));
+                        }
+                    }
+                    if batch.is_empty() { break; }
+
+                    let nodes: Vec<_> = batch.iter().map(|(n, _)| n.clone()).collect();
+                    for ((current, cost), edges) in batch.into_iter().zip(expand_all(self, &nodes, threads)) {
+                        for edge in edges {
+                            let next = edge.dest();
+                            let next_cost = cost.plus(edge.weight());
+                            if result.cost(next).is_none_or(|c| c > next_cost) {
+                                result.reached.insert(next.clone(), (next_cost, Some(current.clone())));
+                                frontier.push(State { priority: next_cost, cost: next_cost, node: next.clone() });
+                            }
+                        }
+                    }
+                }
+                if let Some(goal) = goals.into_iter().min_by_key(|g| result.reached[g].0) {
+                    result.set_goal(goal);
                     break;
                 }
-                debug_assert_eq!(Some(&current.cost), costs.get(&current.node));
-                for edge in self.neighbors(&current.node) {
+            }
+            result
+        }
+
+        // Explores as deeply as possible before backtracking, which finds _a_ route to the goal but
+        // not necessarily the cheapest one. Nodes are visited at most once per canonicalize() key,
+        // so states that are interchangeable for the rest of the search are only explored once.
+        #[allow(dead_code)]
+        fn dfs<K: Eq + Hash>(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                             mut canonicalize: impl FnMut(&Self::Node) -> K) -> SearchResult<Self::Node, Self::Weight> {
+            let mut result: SearchResult<_, Self::Weight> = SearchResult::new(start.clone());
+            let mut visited = HashSet::new();
+            visited.insert(canonicalize(start));
+            let mut stack = vec!(start.clone());
+
+            while let Some(current) = stack.pop() {
+                if goal_predicate(&current) {
+                    result.set_goal(current);
+                    break;
+                }
+                let cost = result.reached[&current].0;
+                for edge in self.neighbors(&current) {
                     let next = edge.dest();
-                    let next_cost = current.cost + edge.weight();
+                    if visited.insert(canonicalize(next)) {
+                        result.reached.insert(next.clone(), (cost.plus(edge.weight()), Some(current.clone())));
+                        stack.push(next.clone());
+                    }
+                }
+            }
+            result
+        }
+
+        // Iterative deepening: repeated depth-limited DFSes, each one edge deeper than the last. Like
+        // BFS this finds the route with the fewest edges, but only the current route is stored, not
+        // every node reached, trading repeated work for memory. Nodes are compared by their
+        // canonicalize() key to avoid looping back onto the current route. The result only
+        // contains the route to the goal, if one is found within max_depth edges.
+        #[allow(dead_code)]
+        fn iddfs<K: Eq + Hash>(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                               max_depth: usize, mut canonicalize: impl FnMut(&Self::Node) -> K) -> SearchResult<Self::Node, Self::Weight> {
+            if goal_predicate(start) {
+                let mut result = SearchResult::new(start.clone());
+                result.set_goal(start.clone());
+                return result;
+            }
+
+            for limit in 1..=max_depth {
+                let mut cut_off = false;
+                let mut route = vec!((start.clone(), Self::Weight::ZERO, self.neighbors(start).into_iter()));
+                let mut on_route = HashSet::new();
+                on_route.insert(canonicalize(start));
+                while let Some((_, cost, edges)) = route.last_mut() {
+                    match edges.next() {
+                        Some(edge) => {
+                            let next = edge.dest();
+                            let key = canonicalize(next);
+                            if on_route.contains(&key) { continue; }
+                            let next_cost = cost.plus(edge.weight());
+                            if goal_predicate(next) {
+                                let route = route.into_iter().map(|(n, c, _)| (n, c)).chain(std::iter::once((next.clone(), next_cost)));
+                                let mut result = SearchResult::from_route(route);
+                                result.set_goal(next.clone());
+                                return result;
+                            }
+                            if route.len() < limit {
+                                on_route.insert(key);
+                                route.push((next.clone(), next_cost, self.neighbors(next).into_iter()));
+                            } else {
+                                cut_off = true;
+                            }
+                        },
+                        None => {
+                            let (node, _, _) = route.pop().expect("Non-empty");
+                            on_route.remove(&canonicalize(&node));
+                        },
+                    }
+                }
+                if !cut_off {
+                    // Nothing was left unexplored, going deeper won't help
+                    return SearchResult::new(start.clone());
+                }
+            }
+            let mut result = SearchResult::new(start.clone());
+            result.stop_reason = StopReason::MaxDepth;
+            result
+        }
 
-                    let prior_next_cost = costs.get(next);
-                    if prior_next_cost.is_none() || *prior_next_cost.expect("Not-none") > next_cost {
-                        costs.insert(next.clone(), next_cost);
-                        frontier.push(State { cost: next_cost, node: next.clone() });
-                        routes.insert(next.clone(), edge.clone());
+        // Finds the cheapest route to a goal by computing, depth-first, the cheapest cost from each
+        // node to a goal, memoised by the node's canonicalize() key. Unlike Dijkstra's algorithm,
+        // which stores every state it reaches, nodes that share a key (e.g. the same position with
+        // the same keys collected) are only explored once. This is only valid for acyclic graphs,
+        // such as those where every edge makes irreversible progress; a cycle panics. The result
+        // only contains the cheapest route to the goal.
+        #[allow(dead_code)]
+        fn memoized_dfs<K: Clone + Eq + Hash>(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool,
+                                              mut canonicalize: impl FnMut(&Self::Node) -> K) -> SearchResult<Self::Node, Self::Weight> {
+            // The cost from each key to a goal and the next node on that route (None for goals), or
+            // None if no goal is reachable
+            let mut memo = HashMap::new();
+            // Each node being explored: its key, its remaining edges, the cheapest route found from
+            // it so far, and the edge waiting on its dest to be explored
+            let mut stack = Vec::new();
+            let mut on_stack = HashSet::new();
+
+            let start_key = canonicalize(start);
+            if goal_predicate(start) {
+                memo.insert(start_key.clone(), Some((Self::Weight::ZERO, None)));
+            } else {
+                on_stack.insert(start_key.clone());
+                stack.push((start_key.clone(), self.neighbors(start).into_iter(), None, None));
+            }
+
+            while let Some((_, edges, best, pending)) = stack.last_mut() {
+                let edge = match pending.take().or_else(|| edges.next()) {
+                    Some(edge) => edge,
+                    None => {
+                        let (key, _, best, _) = stack.pop().expect("Non-empty");
+                        on_stack.remove(&key);
+                        memo.insert(key, best.map(|(cost, next)| (cost, Some(next))));
+                        continue;
+                    },
+                };
+                let next = edge.dest();
+                let key = canonicalize(next);
+                if !memo.contains_key(&key) {
+                    assert!(!on_stack.contains(&key), "memoized_dfs() requires an acyclic graph, found a cycle at {:?}", next);
+                    if goal_predicate(next) {
+                        memo.insert(key.clone(), Some((Self::Weight::ZERO, None)));
+                    } else {
+                        let next_edges = self.neighbors(next).into_iter();
+                        *pending = Some(edge);
+                        on_stack.insert(key.clone());
+                        stack.push((key, next_edges, None, None));
+                        continue;
+                    }
+                }
+                if let Some(Some((remaining, _))) = memo.get(&key) {
+                    let cost = edge.weight().plus(*remaining);
+                    if best.as_ref().is_none_or(|(c, _)| cost < *c) {
+                        *best = Some((cost, next.clone()));
                     }
                 }
             }
 
-            let mut current = goal?;  // return if no goal
-            let mut path = Vec::new();
-            while current != *start {
-                if let Some(next) = routes.get(&current) {
-                    path.push(next.clone());
-                    current = next.source().clone();
-                } else {
-                    unreachable!();
+            let total = match memo[&start_key] {
+                Some((total, _)) => total,
+                None => return SearchResult::new(start.clone()),
+            };
+            let mut route = vec!((start.clone(), Self::Weight::ZERO));
+            let mut current = start_key;
+            while let Some((_, Some(next))) = &memo[&current] {
+                current = canonicalize(next);
+                let next_remaining = memo[&current].as_ref().expect("On route").0;
+                route.push((next.clone(), total.minus(next_remaining)));
+            }
+            let goal = route.last().expect("Non-empty").0.clone();
+            let mut result = SearchResult::from_route(route);
+            result.set_goal(goal);
+            result
+        }
+
+        // Fills outwards from the start, grouping the nodes reached by the number of edges needed to
+        // reach them (regardless of their weights), e.g. how far something has spread each minute.
+        fn flood_fill(&self, start: &Self::Node) -> Vec<Vec<Self::Node>> {
+            let mut seen = HashSet::new();
+            seen.insert(start.clone());
+            let mut layers = vec!(vec!(start.clone()));
+            loop {
+                let next: Vec<_> = layers.last().expect("Non-empty").iter()
+                    .flat_map(|n| self.neighbors(n))
+                    .map(|e| e.dest().clone())
+                    .filter(|n| seen.insert(n.clone()))
+                    .collect();
+                if next.is_empty() { break; }
+                layers.push(next);
+            }
+            layers
+        }
+
+        // Groups the nodes reachable from the given nodes into connected components. Edges are
+        // treated as undirected, i.e. this expects every edge to have a corresponding reverse edge.
+        #[allow(dead_code)]
+        fn connected_components(&self, nodes: &[Self::Node]) -> Vec<Vec<Self::Node>> {
+            let mut seen = HashSet::new();
+            let mut components = Vec::new();
+            for node in nodes {
+                if !seen.insert(node.clone()) { continue; }
+                let mut component = Vec::new();
+                let mut stack = vec!(node.clone());
+                while let Some(current) = stack.pop() {
+                    for edge in self.neighbors(&current) {
+                        if seen.insert(edge.dest().clone()) {
+                            stack.push(edge.dest().clone());
+                        }
+                    }
+                    component.push(current);
                 }
+                components.push(component);
             }
-            path.reverse();
-            Some(path)
+            components
+        }
+
+        // Orders the nodes reachable from the given roots such that every edge leads from an
+        // earlier node to a later one. If the graph has a cycle there is no such order, and one of
+        // the cycles is returned instead.
+        fn topological_sort(&self, roots: &[Self::Node]) -> Result<Vec<Self::Node>, Vec<Self::Node>> {
+            let mut done = HashSet::new();
+            let mut order = Vec::new();
+            for root in roots {
+                if done.contains(root) { continue; }
+                // A depth-first search, tracking the current path to detect cycles
+                let mut path = vec!((root.clone(), self.neighbors(root).into_iter()));
+                let mut on_path = HashSet::new();
+                on_path.insert(root.clone());
+                while let Some((node, edges)) = path.last_mut() {
+                    match edges.next() {
+                        Some(edge) => {
+                            let next = edge.dest();
+                            if on_path.contains(next) {
+                                let cycle_start = path.iter().position(|(n, _)| n == next).expect("On path");
+                                return Err(path.drain(cycle_start..).map(|(n, _)| n).collect());
+                            }
+                            if !done.contains(next) {
+                                on_path.insert(next.clone());
+                                path.push((next.clone(), self.neighbors(next).into_iter()));
+                            }
+                        },
+                        None => {
+                            let node = node.clone();
+                            path.pop();
+                            on_path.remove(&node);
+                            done.insert(node.clone());
+                            order.push(node);
+                        },
+                    }
+                }
+            }
+            // Nodes finish after everything they lead to
+            order.reverse();
+            Ok(order)
+        }
+
+        // Tarjan's algorithm, over the nodes reachable from the given nodes. Components are returned
+        // in reverse topological order, i.e. edges between components only lead to earlier ones.
+        #[allow(dead_code)]
+        fn strongly_connected_components(&self, nodes: &[Self::Node]) -> Vec<Vec<Self::Node>> {
+            // Each visited node's DFS index and "low-link", the lowest index reachable from it
+            let mut indexes: HashMap<Self::Node, (usize, usize)> = HashMap::new();
+            let mut stack = Vec::new();
+            let mut on_stack = HashSet::new();
+            let mut components = Vec::new();
+            for root in nodes {
+                if indexes.contains_key(root) { continue; }
+                indexes.insert(root.clone(), (indexes.len(), indexes.len()));
+                stack.push(root.clone());
+                on_stack.insert(root.clone());
+                let mut path = vec!((root.clone(), self.neighbors(root).into_iter()));
+                while let Some((node, edges)) = path.last_mut() {
+                    match edges.next() {
+                        Some(edge) => {
+                            let next = edge.dest();
+                            match indexes.get(next) {
+                                None => {
+                                    indexes.insert(next.clone(), (indexes.len(), indexes.len()));
+                                    stack.push(next.clone());
+                                    on_stack.insert(next.clone());
+                                    path.push((next.clone(), self.neighbors(next).into_iter()));
+                                },
+                                Some(&(next_index, _)) => if on_stack.contains(next) {
+                                    let low = &mut indexes.get_mut(node).expect("Visited").1;
+                                    *low = (*low).min(next_index);
+                                },
+                            }
+                        },
+                        None => {
+                            let node = node.clone();
+                            path.pop();
+                            let (index, low) = indexes[&node];
+                            if let Some((parent, _)) = path.last() {
+                                let parent_low = &mut indexes.get_mut(parent).expect("Visited").1;
+                                *parent_low = (*parent_low).min(low);
+                            }
+                            if index == low {
+                                let mut component = Vec::new();
+                                loop {
+                                    let member = stack.pop().expect("Node is on the stack");
+                                    on_stack.remove(&member);
+                                    let done = member == node;
+                                    component.push(member);
+                                    if done { break; }
+                                }
+                                components.push(component);
+                            }
+                        },
+                    }
+                }
+            }
+            components
         }
 
-        fn dijkstras_all(&self, start: &Self::Node) -> HashMap<Self::Node, Vec<Edge<Self::Node>>> {
+        // Prim's algorithm: the edges of a minimum spanning tree of the nodes reachable from start.
+        // Like connected_components(), this expects every edge to have a reverse edge.
+        #[allow(dead_code)]
+        fn minimum_spanning_tree(&self, start: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
             let mut frontier = BinaryHeap::new();
-            let mut costs = HashMap::new();
-            let mut routes = HashMap::new();
-            frontier.push(State { cost: 0, node: start.clone() });
-            costs.insert(start.clone(), 0);
-            routes.insert(start.clone(),
-                          Edge::new(0, start.clone(), start.clone())); // careful, potential infinite loop
-
-            while let Some(current) = frontier.pop() {
-                debug_assert_eq!(Some(&current.cost), costs.get(&current.node));
-                for edge in self.neighbors(&current.node) {
-                    let next = edge.dest();
-                    let next_cost = current.cost + edge.weight();
+            let mut in_tree = HashSet::new();
+            let mut tree = Vec::new();
+            let mut node = start.clone();
+            loop {
+                for edge in self.neighbors(&node) {
+                    if !in_tree.contains(edge.dest()) {
+                        frontier.push(State { priority: edge.weight(), cost: edge.weight(), node: edge });
+                    }
+                }
+                in_tree.insert(node);
+                // The cheapest edge leading out of the tree
+                match std::iter::from_fn(|| frontier.pop()).find(|s| !in_tree.contains(s.node.dest())) {
+                    Some(State { node: edge, .. }) => {
+                        node = edge.dest().clone();
+                        tree.push(edge);
+                    },
+                    None => return tree,
+                }
+            }
+        }
+
+        // The Floyd–Warshall algorithm: the cost of the cheapest route between every pair of nodes
+        // reachable from the given nodes, keyed by (source, dest). Pairs with no route are absent.
+        #[allow(dead_code)]
+        fn all_pairs_shortest_paths(&self, nodes: &[Self::Node]) -> HashMap<(Self::Node, Self::Node), Self::Weight> {
+            let mut all = Vec::new();
+            let mut indexes = HashMap::new();
+            let mut stack = nodes.to_vec();
+            while let Some(node) = stack.pop() {
+                if indexes.contains_key(&node) { continue; }
+                indexes.insert(node.clone(), all.len());
+                stack.extend(self.neighbors(&node).into_iter().map(|e| e.dest).filter(|d| !indexes.contains_key(d)));
+                all.push(node);
+            }
 
-                    let prior_next_cost = costs.get(next);
-                    if prior_next_cost.is_none() || *prior_next_cost.expect("Not-none") > next_cost {
-                        costs.insert(next.clone(), next_cost);
-                        frontier.push(State { cost: next_cost, node: next.clone() });
-                        routes.insert(next.clone(), edge.clone());
+            let mut costs: Vec<Vec<Option<Self::Weight>>> = vec!(vec!(None; all.len()); all.len());
+            for (i, node) in all.iter().enumerate() {
+                costs[i][i] = Some(Self::Weight::ZERO);
+                for edge in self.neighbors(node) {
+                    let cost = &mut costs[i][indexes[edge.dest()]];
+                    if cost.is_none_or(|c| edge.weight() < c) {
+                        *cost = Some(edge.weight());
+                    }
+                }
+            }
+            for k in 0..all.len() {
+                let from_k = costs[k].clone();
+                for row in costs.iter_mut() {
+                    let Some(to_k) = row[k] else { continue; };
+                    for (cost, from_k) in row.iter_mut().zip(&from_k) {
+                        let Some(from_k) = from_k else { continue; };
+                        let through_k = to_k.plus(*from_k);
+                        if cost.is_none_or(|c| through_k < c) {
+                            *cost = Some(through_k);
+                        }
                     }
                 }
             }
 
-            let mut paths = HashMap::new();
-            for goal in routes.keys() {
-                let mut path = Vec::new();
-                let mut current = goal.clone();
-                while current != *start {
-                    if let Some(next) = routes.get(&current) {
-                        path.push(next.clone());
-                        current = next.source().clone();
-                    } else {
-                        unreachable!();
+            let mut result = HashMap::new();
+            for (i, source) in all.iter().enumerate() {
+                for (j, dest) in all.iter().enumerate() {
+                    if let Some(cost) = costs[i][j] {
+                        result.insert((source.clone(), dest.clone()), cost);
+                    }
+                }
+            }
+            result
+        }
+    }
+
+    // A graph that can also list the edges leading into a node, which allows searching backwards
+    // from a goal. Searching from both ends at once generally explores far fewer nodes than
+    // searching from one end, since each search only has to cover about half the distance.
+    pub trait ReversibleGraph: Graph {
+        // The edges whose dest is the given node
+        fn predecessors(&self, dest: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>>;
+
+        // Alternately expands a full layer of whichever frontier is smaller. Like bfs(), requires
+        // every edge to have the same weight.
+        fn bidirectional_bfs(&self, start: &Self::Node, goal: &Self::Node) -> SearchResult<Self::Node, Self::Weight> {
+            let mut searches = [SearchResult::new(start.clone()), SearchResult::new(goal.clone())];
+            let mut frontiers = [vec!(start.clone()), vec!(goal.clone())];
+            let mut step = None;
+            if start == goal {
+                let [forward, backward] = searches;
+                return forward.join(start.clone(), &backward);
+            }
+
+            while frontiers.iter().all(|f| !f.is_empty()) {
+                let dir = if frontiers[0].len() <= frontiers[1].len() { 0 } else { 1 };
+                let (this, other) = if dir == 0 {
+                    let [f, b] = &mut searches; (f, &*b)
+                } else {
+                    let [f, b] = &mut searches; (b, &*f)
+                };
+                let mut meeting: Option<(Self::Weight, Self::Node)> = None;
+                let mut next_frontier = Vec::new();
+                for current in std::mem::take(&mut frontiers[dir]) {
+                    let cost = this.reached[&current].0;
+                    for edge in edges(self, &current, dir == 0) {
+                        let weight = *step.get_or_insert(edge.weight());
+                        assert!(edge.weight() == weight, "BFS does not support non-uniform edge weights: {:?}", edge);
+                        let next = if dir == 0 { edge.dest() } else { edge.source() };
+                        if !this.reached.contains_key(next) {
+                            next_frontier.push(next.clone());
+                            this.reached.insert(next.clone(), (cost.plus(weight), Some(current.clone())));
+                        }
+                        if let Some(other_cost) = other.cost(next) {
+                            let total = this.reached[next].0.plus(other_cost);
+                            if meeting.as_ref().is_none_or(|(c, _)| total < *c) {
+                                meeting = Some((total, next.clone()));
+                            }
+                        }
+                    }
+                }
+                frontiers[dir] = next_frontier;
+                // Any shorter route would have been found by an earlier layer
+                if let Some((_, meet)) = meeting {
+                    let [forward, backward] = searches;
+                    return forward.join(meet, &backward);
+                }
+            }
+            let [forward, _] = searches;
+            forward
+        }
+
+        // Runs Dijkstra's algorithm from both ends, always advancing whichever frontier is
+        // cheaper, until no route through the unexplored nodes could beat the best route found.
+        #[allow(dead_code)]
+        fn bidirectional_dijkstras(&self, start: &Self::Node, goal: &Self::Node) -> SearchResult<Self::Node, Self::Weight> {
+            let mut searches = [SearchResult::new(start.clone()), SearchResult::new(goal.clone())];
+            let mut frontiers = [BinaryHeap::new(), BinaryHeap::new()];
+            for (frontier, node) in frontiers.iter_mut().zip(&[start, goal]) {
+                frontier.push(State { priority: Self::Weight::ZERO, cost: Self::Weight::ZERO, node: (*node).clone() });
+            }
+            let mut meeting: Option<(Self::Weight, Self::Node)> =
+                if start == goal { Some((Self::Weight::ZERO, start.clone())) } else { None };
+
+            // An empty frontier means that search is exhausted, and so is the best it can do
+            while let (Some(fwd), Some(bwd)) = (frontiers[0].peek(), frontiers[1].peek()) {
+                if meeting.as_ref().is_some_and(|(c, _)| fwd.cost.plus(bwd.cost) >= *c) { break; }
+                let dir = if fwd.cost <= bwd.cost { 0 } else { 1 };
+                let (this, other) = if dir == 0 {
+                    let [f, b] = &mut searches; (f, &*b)
+                } else {
+                    let [f, b] = &mut searches; (b, &*f)
+                };
+
+                let current = frontiers[dir].pop().expect("Peeked");
+                if this.cost(&current.node).is_some_and(|c| c < current.cost) { continue; }
+                for edge in edges(self, &current.node, dir == 0) {
+                    let next = if dir == 0 { edge.dest() } else { edge.source() };
+                    let next_cost = current.cost.plus(edge.weight());
+                    if this.cost(next).is_none_or(|c| c > next_cost) {
+                        this.reached.insert(next.clone(), (next_cost, Some(current.node.clone())));
+                        frontiers[dir].push(State { priority: next_cost, cost: next_cost, node: next.clone() });
+                    }
+                    if let Some(other_cost) = other.cost(next) {
+                        let total = this.reached[next].0.plus(other_cost);
+                        if meeting.as_ref().is_none_or(|(c, _)| total < *c) {
+                            meeting = Some((total, next.clone()));
+                        }
                     }
                 }
-                path.reverse();
-                paths.insert(goal.clone(), path);
             }
-            paths
+
+            let [forward, backward] = searches;
+            match meeting {
+                Some((_, meet)) => forward.join(meet, &backward),
+                None => forward,
+            }
+        }
+    }
+
+    // The edges out of the node when searching forwards, or into it when searching backwards
+    fn edges<G: ReversibleGraph + ?Sized>(graph: &G, node: &G::Node, forwards: bool) -> Vec<Edge<G::Node, G::Weight>> {
+        if forwards { graph.neighbors(node) } else { graph.predecessors(node) }
+    }
+
+    // The edges out of each of the nodes, in the same order as the nodes, found by splitting them
+    // between the given number of threads
+    fn expand_all<G: Graph + Sync + ?Sized>(graph: &G, nodes: &[G::Node], threads: usize) -> Vec<Vec<Edge<G::Node, G::Weight>>>
+            where G::Node: Send + Sync, G::Weight: Send {
+        if threads <= 1 || nodes.len() <= 1 {
+            return nodes.iter().map(|n| graph.neighbors(n)).collect();
+        }
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = nodes.chunks(nodes.len().div_ceil(threads))
+                .map(|chunk| scope.spawn(move || chunk.iter().map(|n| graph.neighbors(n)).collect::<Vec<_>>()))
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().expect("Search thread panicked")).collect()
+        })
+    }
+
+    // Receives events from a search as it runs, e.g. to collect statistics or to animate it. Every
+    // event defaults to doing nothing; () is an observer that ignores everything.
+    pub trait SearchObserver<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> {
+        // The node's neighbors are about to be explored; frontier_len nodes remain queued
+        fn expanded(&mut self, _node: &N, _frontier_len: usize) {}
+
+        // The node was added to the frontier, either for the first time or with a cheaper cost
+        fn discovered(&mut self, _node: &N) {}
+
+        // An edge led to a node that had already been reached, at no lower cost
+        fn revisited(&mut self, _node: &N) {}
+
+        fn finished(&mut self, _result: &SearchResult<N, W>) {}
+
+        // Called before each node is checked against the goal and expanded, with the cost and depth
+        // (number of edges) of the route to it, allowing the observer to limit the search
+        fn check(&mut self, _node: &N, _cost: W, _depth: usize) -> Control { Control::Continue }
+    }
+
+    impl<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> SearchObserver<N, W> for () {}
+
+    impl<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight, O: SearchObserver<N, W> + ?Sized> SearchObserver<N, W> for &mut O {
+        fn expanded(&mut self, node: &N, frontier_len: usize) { (**self).expanded(node, frontier_len); }
+        fn discovered(&mut self, node: &N) { (**self).discovered(node); }
+        fn revisited(&mut self, node: &N) { (**self).revisited(node); }
+        fn finished(&mut self, result: &SearchResult<N, W>) { (**self).finished(result); }
+        fn check(&mut self, node: &N, cost: W, depth: usize) -> Control { (**self).check(node, cost, depth) }
+    }
+
+    // A pair of observers both observe the search; if either stops (or skips) a node, it's stopped
+    impl<N, W, A, B> SearchObserver<N, W> for (A, B)
+            where N: Clone + std::fmt::Debug + Eq + Hash, W: Weight, A: SearchObserver<N, W>, B: SearchObserver<N, W> {
+        fn expanded(&mut self, node: &N, frontier_len: usize) {
+            self.0.expanded(node, frontier_len);
+            self.1.expanded(node, frontier_len);
+        }
+
+        fn discovered(&mut self, node: &N) {
+            self.0.discovered(node);
+            self.1.discovered(node);
+        }
+
+        fn revisited(&mut self, node: &N) {
+            self.0.revisited(node);
+            self.1.revisited(node);
+        }
+
+        fn finished(&mut self, result: &SearchResult<N, W>) {
+            self.0.finished(result);
+            self.1.finished(result);
+        }
+
+        fn check(&mut self, node: &N, cost: W, depth: usize) -> Control {
+            match (self.0.check(node, cost, depth), self.1.check(node, cost, depth)) {
+                (Control::Stop(reason), _) | (_, Control::Stop(reason)) => Control::Stop(reason),
+                (Control::Skip(reason), _) | (_, Control::Skip(reason)) => Control::Skip(reason),
+                _ => Control::Continue,
+            }
+        }
+    }
+
+    // What a search should do with the node it's about to expand
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Control {
+        Continue,
+        // Don't expand this node, but continue the search
+        Skip(StopReason),
+        // End the search
+        Stop(StopReason),
+    }
+
+    // Why a search ended
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum StopReason {
+        // A goal was found
+        Goal,
+        // Every reachable node was explored
+        Exhausted,
+        // Every reachable node within the limit was explored, but nodes beyond it were skipped
+        MaxCost,
+        MaxDepth,
+        // The search ended early, possibly before exploring every node within its limits
+        MaxExpansions,
+        Cancelled,
+    }
+
+    // The outcome of a search: the cost to reach each node that was reached, and the goal, if one
+    // was found. Rather than storing every route, only each node's predecessor is kept, and routes
+    // are reconstructed on request; an edge's weight is the difference between its nodes' costs.
+    #[derive(Clone, Debug)]
+    pub struct SearchResult<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> {
+        start: N,
+        goal: Option<N>,
+        stop_reason: StopReason,
+        // Searches can reach millions of nodes (e.g. Day 18), so costs and predecessors share a map
+        reached: HashMap<N, (W, Option<N>)>,
+    }
+
+    #[allow(dead_code)]
+    impl<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> SearchResult<N, W> {
+        fn new(start: N) -> SearchResult<N, W> {
+            let mut reached = HashMap::new();
+            reached.insert(start.clone(), (W::ZERO, None));
+            SearchResult { start, goal: None, stop_reason: StopReason::Exhausted, reached }
+        }
+
+        // Combines a forward search with a search backwards from its goal, which meet at the given
+        // node, into a single result whose goal is the backward search's start.
+        fn join(mut self, meet: N, backward: &SearchResult<N, W>) -> SearchResult<N, W> {
+            let total = self.reached[&meet].0.plus(backward.reached[&meet].0);
+            let mut current = meet;
+            while let Some(next) = &backward.reached[&current].1 {
+                let cost = total.minus(backward.reached[next].0);
+                if self.cost(next).is_none_or(|c| c > cost) {
+                    self.reached.insert(next.clone(), (cost, Some(current)));
+                }
+                current = next.clone();
+            }
+            self.set_goal(current);
+            self
+        }
+
+        // A result containing only the given route of nodes and their costs, e.g. from a DFS
+        fn from_route(route: impl IntoIterator<Item = (N, W)>) -> SearchResult<N, W> {
+            let mut route = route.into_iter();
+            let (start, _) = route.next().expect("Empty route");
+            let mut result = SearchResult::new(start.clone());
+            let mut previous = start;
+            for (node, cost) in route {
+                result.reached.insert(node.clone(), (cost, Some(previous)));
+                previous = node;
+            }
+            result
+        }
+
+        fn set_goal(&mut self, goal: N) {
+            self.goal = Some(goal);
+            self.stop_reason = StopReason::Goal;
+        }
+
+        pub fn goal(&self) -> Option<&N> { self.goal.as_ref() }
+
+        // Why the search ended; unless the goal was found or every node explored, the result is partial
+        pub fn stop_reason(&self) -> StopReason { self.stop_reason }
+
+        pub fn goal_cost(&self) -> Option<W> {
+            self.goal.as_ref().and_then(|g| self.cost(g))
+        }
+
+        pub fn goal_path(&self) -> Option<Vec<Edge<N, W>>> {
+            self.goal.as_ref().and_then(|g| self.path(g))
+        }
+
+        // The cost of the cheapest route found to the node, if it was reached
+        pub fn cost(&self, node: &N) -> Option<W> {
+            self.reached.get(node).map(|&(cost, _)| cost)
+        }
+
+        // Every node reached, with its cost
+        pub fn costs(&self) -> impl Iterator<Item = (&N, W)> + '_ {
+            self.reached.iter().map(|(node, &(cost, _))| (node, cost))
+        }
+
+        pub fn reached(&self) -> usize { self.reached.len() }
+
+        pub fn max_cost(&self) -> Option<W> {
+            self.costs().map(|(_, cost)| cost).max()
+        }
+
+        // The edges of the cheapest route found to the node, if it was reached
+        pub fn path(&self, node: &N) -> Option<Vec<Edge<N, W>>> {
+            let mut path = Vec::new();
+            let mut current = node;
+            let mut reached = self.reached.get(node)?;
+            while let (cost, Some(source)) = reached {
+                let source_reached = &self.reached[source];
+                path.push(Edge::new(cost.minus(source_reached.0), source.clone(), current.clone()));
+                current = source;
+                reached = source_reached;
+            }
+            path.reverse();
+            Some(path)
+        }
+
+        // The nodes along the cheapest route found to the node, including the start and the node
+        pub fn nodes(&self, node: &N) -> Option<Vec<N>> {
+            self.path(node).map(|path|
+                std::iter::once(self.start.clone()).chain(path.into_iter().map(|e| e.dest)).collect())
         }
     }
 
     #[derive(Copy, Clone, Debug)]
-    pub struct Edge<N: Clone + std::fmt::Debug> {
-        weight: i32,
+    pub struct Edge<N: Clone + std::fmt::Debug, W: Weight> {
+        weight: W,
         source: N,
         dest: N,
     }
 
-    impl<N: Clone + std::fmt::Debug> Edge<N> {
-        pub fn new(weight: i32, source: N, dest: N) -> Edge<N> {
+    impl<N: Clone + std::fmt::Debug, W: Weight> Edge<N, W> {
+        pub fn new(weight: W, source: N, dest: N) -> Edge<N, W> {
             Edge { weight, source, dest }
         }
 
-        pub fn weight(&self) -> i32 { self.weight }
+        pub fn weight(&self) -> W { self.weight }
         pub fn source(&self) -> &N { &self.source }
         pub fn dest(&self) -> &N { &self.dest }
+
+        // The same edge traversed in the opposite direction, e.g. to list the predecessors of an
+        // undirected graph's nodes
+        pub fn reversed(&self) -> Edge<N, W> {
+            Edge::new(self.weight, self.dest.clone(), self.source.clone())
+        }
+    }
+
+    // The cost of traversing an edge. Costs are summed along a route, and routes are compared by
+    // their total cost, so a weight can be anything with a zero, addition, and a total order.
+    pub trait Weight: Copy + std::fmt::Debug + Ord {
+        const ZERO: Self;
+
+        fn plus(self, other: Self) -> Self;
+        fn minus(self, other: Self) -> Self;
+    }
+
+    macro_rules! int_weight {
+        ($($t:ty),*) => { $(
+            impl Weight for $t {
+                const ZERO: Self = 0;
+
+                fn plus(self, other: Self) -> Self { self + other }
+                fn minus(self, other: Self) -> Self { self - other }
+            }
+        )* }
+    }
+    int_weight!(i32, i64, u32, u64, usize);
+
+    // Pairs are compared lexicographically, so e.g. (doors, steps) finds the route through the
+    // fewest doors, and the shortest such route.
+    impl<A: Weight, B: Weight> Weight for (A, B) {
+        const ZERO: Self = (A::ZERO, B::ZERO);
+
+        fn plus(self, other: Self) -> Self { (self.0.plus(other.0), self.1.plus(other.1)) }
+        fn minus(self, other: Self) -> Self { (self.0.minus(other.0), self.1.minus(other.1)) }
+    }
+
+    // An f64 with a total order (via f64::total_cmp), for non-integral weights like Euclidean distance
+    #[allow(dead_code)]
+    #[derive(Copy, Clone, Debug)]
+    pub struct OrderedFloat(pub f64);
+
+    impl Weight for OrderedFloat {
+        const ZERO: Self = OrderedFloat(0.0);
+
+        fn plus(self, other: Self) -> Self { OrderedFloat(self.0 + other.0) }
+        fn minus(self, other: Self) -> Self { OrderedFloat(self.0 - other.0) }
+    }
+
+    impl PartialEq for OrderedFloat {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for OrderedFloat {}
+
+    impl Ord for OrderedFloat {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.total_cmp(&other.0)
+        }
+    }
+
+    impl PartialOrd for OrderedFloat {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
     }
 
+    // Frontier entries are ordered by priority, which is the cost for Dijkstra's algorithm, or the
+    // cost plus the estimated remaining cost for A*
     #[derive(Copy, Clone, Debug)]
-    struct State<N: Clone + std::fmt::Debug> {
-        cost: i32,
+    struct State<N: Clone + std::fmt::Debug, W: Weight> {
+        priority: W,
+        cost: W,
         node: N,
     }
 
     // We don't implement Eq because it's not well defined, but Ord requires it exist
-    impl<N: Clone + std::fmt::Debug> PartialEq for State<N> {
+    impl<N: Clone + std::fmt::Debug, W: Weight> PartialEq for State<N, W> {
         fn eq(&self, _: &Self) -> bool {
             unimplemented!()
         }
     }
 
-    impl<N: Clone + std::fmt::Debug> Eq for State<N> {}
+    impl<N: Clone + std::fmt::Debug, W: Weight> Eq for State<N, W> {}
 
-    impl<N: Clone + std::fmt::Debug> Ord for State<N> {
-        fn cmp(&self, other: &State<N>) -> Ordering {
-            other.cost.cmp(&self.cost)
+    impl<N: Clone + std::fmt::Debug, W: Weight> Ord for State<N, W> {
+        fn cmp(&self, other: &State<N, W>) -> Ordering {
+            other.priority.cmp(&self.priority)
         }
     }
 
-    impl<N: Clone + std::fmt::Debug> PartialOrd for State<N> {
-        fn partial_cmp(&self, other: &State<N>) -> Option<Ordering> {
+    impl<N: Clone + std::fmt::Debug, W: Weight> PartialOrd for State<N, W> {
+        fn partial_cmp(&self, other: &State<N, W>) -> Option<Ordering> {
             Some(self.cmp(other))
         }
     }
 }
-pub use self::internals::{Edge,Graph};
+pub use self::internals::{Edge,Graph,ReversibleGraph,SearchObserver,SearchResult,StopReason};
+#[allow(unused_imports)]
+pub use self::internals::Control;
+#[allow(unused_imports)]
+pub use self::internals::{OrderedFloat,Weight};
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::euclid::{point,Point,vector};
-    use std::collections::{HashSet, BTreeMap};
+mod adjacency {
+    use std::collections::{HashMap, HashSet};
+    use std::hash::Hash;
+    use super::{Edge, Graph, Weight};
 
-    struct BasicGraph {
-        blocked: HashSet<Point>,
+    // A graph whose edges are stored explicitly, rather than computed on demand. Exploring an
+    // implicit graph once up front avoids recomputing each node's neighbors on every visit, and
+    // allows transforming the graph, e.g. with compress().
+    #[derive(Clone, Debug)]
+    pub struct AdjacencyGraph<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> {
+        edges: HashMap<N, Vec<Edge<N, W>>>,
     }
 
-    impl BasicGraph {
-        fn new(blocked: &[Point]) -> BasicGraph {
-            BasicGraph { blocked: blocked.iter().cloned().collect() }
+    #[allow(dead_code)]
+    impl<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> AdjacencyGraph<N, W> {
+        // Records the edges of every node reachable from the given starting nodes
+        pub fn explore<G: Graph<Node = N, Weight = W>>(graph: &G, starts: &[N]) -> AdjacencyGraph<N, W> {
+            let mut edges = HashMap::new();
+            let mut frontier = starts.to_vec();
+            while let Some(node) = frontier.pop() {
+                if edges.contains_key(&node) { continue; }
+                let neighbors = graph.neighbors(&node);
+                frontier.extend(neighbors.iter().map(|e| e.dest()).filter(|d| !edges.contains_key(*d)).cloned());
+                edges.insert(node, neighbors);
+            }
+            AdjacencyGraph { edges }
         }
-    }
 
-    impl Graph for BasicGraph {
-        type Node = Point;
+        pub fn nodes(&self) -> impl Iterator<Item = &N> + '_ {
+            self.edges.keys()
+        }
 
-        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-            if self.blocked.contains(source) { return vec!(); }
+        pub fn len(&self) -> usize { self.edges.len() }
 
-            vec!(vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)).iter()
-                .map(|v| source + v)
-                .filter(|p| !self.blocked.contains(p))
-                .map(|d| Edge::new(1, source.clone(), d.clone()))
-                .collect()
+        pub fn is_empty(&self) -> bool { self.edges.is_empty() }
+
+        pub fn edge_count(&self) -> usize {
+            self.edges.values().map(|e| e.len()).sum()
         }
-    }
 
-    #[test]
-    fn direct() {
-        let graph = BasicGraph::new(&vec!());
-        let start = point(1, 1);
-        let goal = point(3, 4);
+        // Collapses corridors - chains of nodes with exactly two edges - into single edges whose
+        // weight is the corridor's length, leaving only junctions, dead ends, and the nodes keep()
+        // selects (e.g. keys or doors). Expects an undirected graph, i.e. one where every edge has a
+        // corresponding edge in the opposite direction.
+        pub fn compress(&self, mut keep: impl FnMut(&N) -> bool) -> AdjacencyGraph<N, W> {
+            let corridors: HashSet<_> = self.edges.iter()
+                .filter(|(n, e)| e.len() == 2 && !keep(n))
+                .map(|(n, _)| n)
+                .collect();
 
-        let bfs_route = graph.bfs(&start, |n| n == &goal).unwrap();
-        assert_eq!(bfs_route.len(), 6);
-        assert_eq!(bfs_route[0], start);
-        assert_eq!(bfs_route[bfs_route.len()-1], goal);
+            let mut edges = HashMap::new();
+            for (source, source_edges) in self.edges.iter().filter(|(n, _)| !corridors.contains(n)) {
+                let mut compressed: Vec<Edge<N, W>> = Vec::new();
+                'edges: for edge in source_edges {
+                    let (mut prev, mut current, mut weight) = (source, edge.dest(), edge.weight());
+                    while corridors.contains(current) {
+                        match self.edges[current].iter().find(|e| e.dest() != prev) {
+                            Some(next) => {
+                                weight = weight.plus(next.weight());
+                                prev = current;
+                                current = next.dest();
+                            },
+                            None => continue 'edges, // both edges lead back the way we came
+                        }
+                    }
+                    if current == source { continue; }
+                    match compressed.iter_mut().find(|e| e.dest() == current) {
+                        Some(existing) => if weight < existing.weight() {
+                            *existing = Edge::new(weight, source.clone(), current.clone());
+                        },
+                        None => compressed.push(Edge::new(weight, source.clone(), current.clone())),
+                    }
+                }
+                edges.insert(source.clone(), compressed);
+            }
+            AdjacencyGraph { edges }
+        }
+    }
 
-        let djk_route = graph.dijkstras(&start, |n| n == &goal).unwrap();
-        assert_eq!(djk_route.len(), 5);
+    impl<N: Clone + std::fmt::Debug + Eq + Hash, W: Weight> Graph for AdjacencyGraph<N, W> {
+        type Node = N;
+        type Weight = W;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            self.edges.get(source).cloned().unwrap_or_default()
+        }
+    }
+}
+pub use self::adjacency::AdjacencyGraph;
+
+mod regions {
+    use std::collections::HashSet;
+    use crate::euclid::{Direction4, Grid, Point};
+    use super::{Edge, Graph};
+
+    // A set of grid cells, e.g. a connected region of a map
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Region {
+        cells: HashSet<Point>,
+    }
+
+    #[allow(dead_code)]
+    impl Region {
+        pub fn new(cells: impl IntoIterator<Item = Point>) -> Region {
+            Region { cells: cells.into_iter().collect() }
+        }
+
+        pub fn cells(&self) -> impl Iterator<Item = &Point> + '_ { self.cells.iter() }
+
+        pub fn contains(&self, p: Point) -> bool { self.cells.contains(&p) }
+
+        pub fn area(&self) -> usize { self.cells.len() }
+
+        // The number of cell sides separating a cell in the region from one outside it
+        pub fn perimeter(&self) -> usize {
+            self.cells.iter()
+                .flat_map(|p| Direction4::ALL.iter().map(move |d| p + d.vector()))
+                .filter(|n| !self.cells.contains(n))
+                .count()
+        }
+    }
+
+    // Orthogonally adjacent cells with equal values
+    struct SameValue<'a, T>(&'a Grid<T>);
+
+    impl<'a, T: Eq> Graph for SameValue<'a, T> {
+        type Node = Point;
+        type Weight = i32;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            let value = &self.0[*source];
+            self.0.neighbors4(*source)
+                .filter(|&n| &self.0[n] == value)
+                .map(|n| Edge::new(1, *source, n))
+                .collect()
+        }
+    }
+
+    // Splits the grid into regions of orthogonally adjacent cells with equal values, ordered by
+    // their first cell in row-major order.
+    pub fn regions<T: Eq>(grid: &Grid<T>) -> Vec<Region> {
+        SameValue(grid).connected_components(&grid.points().collect::<Vec<_>>()).into_iter()
+            .map(Region::new)
+            .collect()
+    }
+
+    // Labels each cell with the index of its region in regions()
+    #[allow(dead_code)]
+    pub fn label_regions<T: Eq>(grid: &Grid<T>) -> Grid<usize> {
+        let mut labels = Grid::new(grid.bounds().0, grid.width(), grid.height(), 0);
+        for (i, region) in regions(grid).iter().enumerate() {
+            for cell in region.cells() {
+                labels[*cell] = i;
+            }
+        }
+        labels
+    }
+}
+#[allow(unused_imports)]
+pub use self::regions::{label_regions, regions, Region};
+
+mod observers {
+    use std::fmt;
+    use std::hash::Hash;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use crate::console::Renderer;
+    use crate::euclid::{Point, SparseGrid};
+    use super::{Control, SearchObserver, SearchResult, StopReason, Weight};
+
+    // Counts the work a search does
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct SearchStats {
+        pub expanded: usize,
+        pub discovered: usize,
+        pub revisited: usize,
+        pub max_frontier: usize,
+    }
+
+    impl<N: Clone + fmt::Debug + Eq + Hash, W: Weight> SearchObserver<N, W> for SearchStats {
+        fn expanded(&mut self, _node: &N, frontier_len: usize) {
+            self.expanded += 1;
+            self.max_frontier = self.max_frontier.max(frontier_len);
+        }
+
+        fn discovered(&mut self, _node: &N) { self.discovered += 1; }
+
+        fn revisited(&mut self, _node: &N) { self.revisited += 1; }
+    }
+
+    impl fmt::Display for SearchStats {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Expanded {} nodes, discovered {} ({} revisits); the frontier peaked at {}",
+                   self.expanded, self.discovered, self.revisited, self.max_frontier)
+        }
+    }
+
+    // Bounds how much of a graph a search explores, e.g. so searches of infinite graphs terminate.
+    // Nodes beyond the max cost or depth are skipped, rather than expanded, but the search continues
+    // with the rest of the frontier; the max expansions and cancellation end the search outright.
+    // Either way the search returns the partial result, with the reason it stopped.
+    #[derive(Clone, Debug)]
+    pub struct SearchLimits<W: Weight> {
+        max_cost: Option<W>,
+        max_depth: Option<usize>,
+        max_expansions: Option<usize>,
+        cancellation: Option<CancellationToken>,
+        expansions: usize,
+    }
+
+    #[allow(dead_code)]
+    impl<W: Weight> SearchLimits<W> {
+        pub fn max_cost(mut self, max_cost: W) -> SearchLimits<W> {
+            self.max_cost = Some(max_cost);
+            self
+        }
+
+        // The most edges a route may have
+        pub fn max_depth(mut self, max_depth: usize) -> SearchLimits<W> {
+            self.max_depth = Some(max_depth);
+            self
+        }
+
+        pub fn max_expansions(mut self, max_expansions: usize) -> SearchLimits<W> {
+            self.max_expansions = Some(max_expansions);
+            self
+        }
+
+        pub fn cancellation(mut self, token: &CancellationToken) -> SearchLimits<W> {
+            self.cancellation = Some(token.clone());
+            self
+        }
+    }
+
+    impl<W: Weight> Default for SearchLimits<W> {
+        fn default() -> Self {
+            SearchLimits { max_cost: None, max_depth: None, max_expansions: None, cancellation: None, expansions: 0 }
+        }
+    }
+
+    impl<N: Clone + fmt::Debug + Eq + Hash, W: Weight> SearchObserver<N, W> for SearchLimits<W> {
+        fn expanded(&mut self, _node: &N, _frontier_len: usize) {
+            self.expansions += 1;
+        }
+
+        fn check(&mut self, _node: &N, cost: W, depth: usize) -> Control {
+            if self.cancellation.as_ref().is_some_and(|c| c.is_cancelled()) {
+                return Control::Stop(StopReason::Cancelled);
+            }
+            if self.max_expansions.is_some_and(|m| self.expansions >= m) {
+                return Control::Stop(StopReason::MaxExpansions);
+            }
+            if self.max_cost.is_some_and(|m| cost > m) {
+                return Control::Skip(StopReason::MaxCost);
+            }
+            if self.max_depth.is_some_and(|m| depth > m) {
+                return Control::Skip(StopReason::MaxDepth);
+            }
+            Control::Continue
+        }
+    }
+
+    // A flag to cancel searches, e.g. from another thread; clones share the same flag
+    #[derive(Clone, Debug, Default)]
+    pub struct CancellationToken {
+        cancelled: Arc<AtomicBool>,
+    }
+
+    #[allow(dead_code)]
+    impl CancellationToken {
+        pub fn cancel(&self) {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+
+        pub fn is_cancelled(&self) -> bool {
+            self.cancelled.load(Ordering::Relaxed)
+        }
+    }
+
+    // Animates a search over Points, painting each visited cell as VISITED, cells on the frontier
+    // as FRONTIER, and finally the route to the goal (if one was found) as PATH. Any other cell is
+    // painted by the background function, e.g. to show walls.
+    pub struct SearchRenderer<'a> {
+        renderer: Renderer,
+        cells: SparseGrid<char>,
+        background: Box<dyn Fn(Point) -> char + 'a>,
+        interval: usize,
+        expansions: usize,
+    }
+
+    #[allow(dead_code)]
+    impl<'a> SearchRenderer<'a> {
+        pub const VISITED: char = '·';
+        pub const FRONTIER: char = '+';
+        pub const PATH: char = 'o';
+
+        pub fn new(renderer: Renderer, background: impl Fn(Point) -> char + 'a) -> SearchRenderer<'a> {
+            SearchRenderer { renderer, cells: SparseGrid::new(), background: Box::new(background), interval: 1, expansions: 0 }
+        }
+
+        // Draws a frame every n expansions, rather than after each one
+        pub fn every(mut self, n: usize) -> SearchRenderer<'a> {
+            self.interval = n.max(1);
+            self
+        }
+
+        // Leaves the final frame (showing the route) on screen
+        pub fn finish(self) {
+            let frame = self.frame();
+            self.renderer.finish(frame);
+        }
+
+        // The search as painted so far, covering every cell the search has reached
+        pub fn frame(&self) -> String {
+            self.cells.render(|p, c| c.cloned().unwrap_or_else(|| (self.background)(p)))
+        }
+
+        fn draw(&mut self) {
+            if !self.renderer.enabled() { return; }
+            let frame = self.frame();
+            self.renderer.draw(frame);
+        }
+    }
+
+    impl<'a, W: Weight> SearchObserver<Point, W> for SearchRenderer<'a> {
+        fn expanded(&mut self, node: &Point, _frontier_len: usize) {
+            if !self.renderer.enabled() { return; }
+            self.cells.insert(*node, Self::VISITED);
+            self.expansions += 1;
+            if self.expansions.is_multiple_of(self.interval) {
+                self.draw();
+            }
+        }
+
+        fn discovered(&mut self, node: &Point) {
+            if !self.renderer.enabled() { return; }
+            self.cells.insert(*node, Self::FRONTIER);
+        }
+
+        fn finished(&mut self, result: &SearchResult<Point, W>) {
+            if !self.renderer.enabled() { return; }
+            for node in result.goal().and_then(|g| result.nodes(g)).unwrap_or_default() {
+                self.cells.insert(node, Self::PATH);
+            }
+            self.draw();
+        }
+    }
+}
+pub use self::observers::{SearchLimits, SearchStats};
+#[allow(unused_imports)]
+pub use self::observers::{CancellationToken, SearchRenderer};
+
+mod heuristics {
+    use crate::euclid::Point;
+
+    // Admissible A* heuristics for graphs of Points, as long as no edge moves further than its weight
+    // in the respective metric, e.g. Manhattan for orthogonal unit steps.
+    pub fn manhattan(goal: Point) -> impl Fn(&Point) -> i32 {
+        move |p| (goal - *p).grid_len()
+    }
+
+    #[allow(dead_code)]
+    pub fn euclidean(goal: Point) -> impl Fn(&Point) -> i32 {
+        // Rounding down keeps the estimate from exceeding the true distance
+        move |p| (goal - *p).len() as i32
+    }
+}
+pub use self::heuristics::manhattan;
+#[allow(unused_imports)]
+pub use self::heuristics::euclidean;
+
+// Wraps a graph to count how many nodes a search expands, to compare search strategies
+#[cfg(test)]
+pub struct CountingGraph<'a, G: Graph> {
+    graph: &'a G,
+    expanded: std::cell::Cell<usize>,
+}
+
+#[cfg(test)]
+impl<'a, G: Graph> CountingGraph<'a, G> {
+    pub fn new(graph: &'a G) -> CountingGraph<'a, G> {
+        CountingGraph { graph, expanded: std::cell::Cell::new(0) }
+    }
+
+    // Returns the number of nodes expanded so far, and resets the count
+    pub fn take(&self) -> usize {
+        self.expanded.replace(0)
+    }
+}
+
+#[cfg(test)]
+impl<'a, G: Graph> Graph for CountingGraph<'a, G> {
+    type Node = G::Node;
+    type Weight = G::Weight;
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        self.expanded.set(self.expanded.get() + 1);
+        self.graph.neighbors(source)
+    }
+}
+
+#[cfg(test)]
+impl<'a, G: ReversibleGraph> ReversibleGraph for CountingGraph<'a, G> {
+    fn predecessors(&self, dest: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+        self.expanded.set(self.expanded.get() + 1);
+        self.graph.predecessors(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::euclid::{point,Direction4,Direction8,Grid,Point,vector};
+    use std::collections::{HashMap, HashSet, BTreeMap};
+
+    struct BasicGraph {
+        blocked: HashSet<Point>,
+    }
+
+    impl BasicGraph {
+        fn new(blocked: &[Point]) -> BasicGraph {
+            BasicGraph { blocked: blocked.iter().cloned().collect() }
+        }
+    }
+
+    impl Graph for BasicGraph {
+        type Node = Point;
+        type Weight = i32;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            if self.blocked.contains(source) { return vec!(); }
+
+            [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
+                .map(|v| source + v)
+                .filter(|p| !self.blocked.contains(p))
+                .map(|d| Edge::new(1, *source, d))
+                .collect()
+        }
+    }
+
+    impl ReversibleGraph for BasicGraph {
+        fn predecessors(&self, dest: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            self.neighbors(dest).iter().map(Edge::reversed).collect()
+        }
+    }
+
+    // Moves in all eight directions, with weights given by the cost function
+    struct CostGraph<W: Weight, F: Fn(Point, Point) -> W> {
+        cost: F,
+    }
+
+    impl<W: Weight, F: Fn(Point, Point) -> W> Graph for CostGraph<W, F> {
+        type Node = Point;
+        type Weight = W;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            Direction8::ALL.iter()
+                .map(|d| source + d.vector())
+                .filter(|p| p.x.abs() <= 5 && p.y.abs() <= 5)
+                .map(|d| Edge::new((self.cost)(*source, d), *source, d))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn lexicographic_weights() {
+        // (doors, steps): the column x == 0 is a wall of doors, except at the edge
+        let graph = CostGraph { cost: |_, d: Point| (if d.x == 0 && d.y < 5 { 1 } else { 0 }, 1) };
+        let (start, goal) = (point(-2, 0), point(2, 0));
+
+        let route = graph.dijkstras(&start, |n| n == &goal);
+        assert_eq!(route.goal_cost(), Some((0, 10)));
+        assert!(route.nodes(&goal).unwrap().contains(&point(0, 5)));
+
+        let through_door = CostGraph { cost: |_, d: Point| (1, if d.x == 0 && d.y < 5 { 1 } else { 0 }) };
+        assert_eq!(through_door.dijkstras(&start, |n| n == &goal).goal_cost(), Some((4, 1)));
+    }
+
+    #[test]
+    fn float_weights() {
+        let graph = CostGraph { cost: |s: Point, d: Point| OrderedFloat((d - s).len()) };
+        let (start, goal) = (point(0, 0), point(3, 1));
+
+        let djk = graph.dijkstras(&start, |n| n == &goal);
+        let astar = graph.astar(&start, |n| n == &goal, |n| OrderedFloat((goal - *n).len()));
+        let expected = 2.0 + 2f64.sqrt();
+        assert!((djk.goal_cost().unwrap().0 - expected).abs() < 1e-9);
+        assert_eq!(astar.goal_cost(), djk.goal_cost());
+        assert_eq!(astar.goal_path().unwrap().len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-uniform")]
+    fn bfs_requires_uniform_weights() {
+        let graph = CostGraph { cost: |s: Point, d: Point| (d - s).grid_len() };
+        graph.bfs_all(&point(0, 0));
+    }
+
+    #[test]
+    fn direct() {
+        let graph = BasicGraph::new(&[]);
+        let start = point(1, 1);
+        let goal = point(3, 4);
+
+        let bfs = graph.bfs(&start, |n| n == &goal);
+        assert_eq!(bfs.goal(), Some(&goal));
+        assert_eq!(bfs.goal_cost(), Some(5));
+        let bfs_route = bfs.nodes(&goal).unwrap();
+        assert_eq!(bfs_route.len(), 6);
+        assert_eq!(bfs_route[0], start);
+        assert_eq!(bfs_route[bfs_route.len()-1], goal);
+
+        let djk_route = graph.dijkstras(&start, |n| n == &goal).goal_path().unwrap();
+        assert_eq!(djk_route.len(), 5);
         assert_eq!(djk_route[0].source(), &start);
         assert_eq!(djk_route[djk_route.len()-1].dest(), &goal);
     }
 
     #[test]
     fn wall() {
-        let graph = BasicGraph::new(&vec!(
-            point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)
-        ));
+        let graph = BasicGraph::new(&[point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)]);
         let start = point(1, 1);
         let goal = point(3, 4);
 
-        let bfs_route = graph.bfs(&start, |n| n == &goal).unwrap();
+        let bfs = graph.bfs(&start, |n| n == &goal);
+        assert_eq!(bfs.goal_cost(), Some(9));
+        let bfs_route = bfs.nodes(&goal).unwrap();
         assert_eq!(bfs_route.len(), 10);
         assert_eq!(bfs_route[0], start);
         assert_eq!(bfs_route[bfs_route.len()-1], goal);
 
-        let djk_route = graph.dijkstras(&start, |n| n == &goal).unwrap();
+        let djk_route = graph.dijkstras(&start, |n| n == &goal).goal_path().unwrap();
         assert_eq!(djk_route.len(), 9);
         assert_eq!(djk_route[0].source(), &start);
         assert_eq!(djk_route[djk_route.len()-1].dest(), &goal);
     }
 
+    parameterized_test::create!{ astar, (blocked, goal), {
+        let graph = BasicGraph::new(blocked);
+        let counter = CountingGraph::new(&graph);
+        let start = point(0, 0);
+        let goal: Point = goal;
+
+        let djk_cost = counter.dijkstras(&start, |n| n == &goal).goal_cost();
+        let djk_expanded = counter.take();
+        for heuristic in &[&manhattan(goal) as &dyn Fn(&Point) -> i32, &euclidean(goal)] {
+            let astar = counter.astar(&start, |n| n == &goal, heuristic);
+            assert_eq!(astar.goal_cost(), djk_cost);
+            assert_eq!(astar.goal_path().unwrap().last().unwrap().dest(), &goal);
+            let astar_expanded = counter.take();
+            assert!(astar_expanded < djk_expanded, "A* expanded {} nodes, Dijkstra's {}", astar_expanded, djk_expanded);
+        }
+    }}
+    astar! {
+        open: (&[], point(5, 7)),
+        wall: (&[point(-1, 3), point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)], point(3, 6)),
+    }
+
+    #[test]
+    fn astar_unreachable() {
+        let graph = BasicGraph::new(&[point(1, 0), point(-1, 0), point(0, 1), point(0, -1)]);
+        let astar = graph.astar(&point(0, 0), |n| n == &point(3, 3), manhattan(point(3, 3)));
+        assert_eq!(astar.goal(), None);
+        assert_eq!(astar.stop_reason(), StopReason::Exhausted);
+        assert!(astar.goal_path().is_none());
+        assert_eq!(astar.reached(), 1);
+    }
+
+    parameterized_test::create!{ parallel_bfs, threads, {
+        let graph = BasicGraph::new(&[point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)]);
+        let (start, goal) = (point(1, 1), point(3, 4));
+
+        let bfs = graph.bfs(&start, |n| n == &goal);
+        let parallel = graph.parallel_bfs(&start, |n| n == &goal, threads);
+        assert_eq!(parallel.goal_cost(), Some(9));
+        assert_eq!(parallel.nodes(&goal), bfs.nodes(&goal));
+        assert_eq!(parallel.costs().collect::<HashMap<_, _>>(), bfs.costs().collect::<HashMap<_, _>>());
+    }}
+    parallel_bfs! {
+        one: 1,
+        two: 2,
+        many: 16,
+    }
+
+    parameterized_test::create!{ parallel_dijkstras, (delta, threads), {
+        // Diagonal steps cost more than orthogonal ones
+        let graph = CostGraph { cost: |s: Point, d: Point| (d - s).grid_len() * 3 - 1 };
+        let start = point(-3, -4);
+
+        let expected: HashMap<_, _> = graph.dijkstras_all(&start).costs().map(|(&n, c)| (n, c)).collect();
+        let parallel = graph.parallel_dijkstras(&start, |_| false, delta, threads);
+        assert_eq!(parallel.costs().map(|(&n, c)| (n, c)).collect::<HashMap<_, _>>(), expected);
+        for (node, cost) in parallel.costs() {
+            let path = parallel.path(node).unwrap();
+            assert_eq!(path.iter().map(|e| e.weight()).sum::<i32>(), cost);
+        }
+
+        for goal in &[point(4, 5), point(-3, -4), point(0, 0)] {
+            let route = graph.parallel_dijkstras(&start, |n| n == goal, delta, threads);
+            assert_eq!(route.goal_cost(), Some(expected[goal]));
+            assert_eq!(route.goal_path().unwrap().last().map_or(start, |e| *e.dest()), *goal);
+        }
+    }}
+    parallel_dijkstras! {
+        sequential: (0, 1),
+        no_delta: (0, 4),
+        small_delta: (2, 4),
+        large_delta: (50, 4),
+        single_threaded: (7, 1),
+    }
+
+    #[test]
+    fn zero_one_bfs() {
+        // Moving along the x-axis is free
+        let graph = CostGraph { cost: |_, d: Point| if d.y == 0 { 0 } else { 1 } };
+        let (start, goal) = (point(-5, 3), point(5, 3));
+
+        let bfs = graph.bfs_01(&start, |n| n == &goal);
+        assert_eq!(bfs.goal_cost(), Some(5));
+        assert_eq!(bfs.goal_cost(), graph.dijkstras(&start, |n| n == &goal).goal_cost());
+        assert!(bfs.nodes(&goal).unwrap().contains(&point(0, 0)));
+        assert_eq!(graph.bfs_01(&start, |_| false).max_cost(), graph.dijkstras_all(&start).max_cost());
+    }
+
+    #[test]
+    #[should_panic(expected = "more than one non-zero")]
+    fn zero_one_bfs_requires_two_weights() {
+        let graph = CostGraph { cost: |s: Point, d: Point| (d - s).grid_len() };
+        graph.bfs_01(&point(0, 0), |_| false);
+    }
+
+    parameterized_test::create!{ bidirectional, (blocked, goal), {
+        let graph = BasicGraph::new(blocked);
+        let counter = CountingGraph::new(&graph);
+        let start = point(0, 0);
+        let goal: Point = goal;
+
+        let bfs = counter.bfs(&start, |n| n == &goal);
+        let bfs_expanded = counter.take();
+        let bidi_bfs = counter.bidirectional_bfs(&start, &goal);
+        let bidi_expanded = counter.take();
+        let bidi_djk = graph.bidirectional_dijkstras(&start, &goal);
+        for result in &[&bidi_bfs, &bidi_djk] {
+            assert_eq!(result.goal(), Some(&goal));
+            assert_eq!(result.goal_cost(), bfs.goal_cost());
+            let nodes = result.nodes(&goal).unwrap();
+            assert_eq!(nodes.len() as i32, bfs.goal_cost().unwrap() + 1);
+            assert_eq!(nodes[0], start);
+            assert!(nodes.windows(2).all(|w| (w[1] - w[0]).grid_len() == 1 && !blocked.contains(&w[1])));
+        }
+        assert!(bidi_expanded < bfs_expanded, "Bidirectional BFS expanded {} nodes, BFS {}", bidi_expanded, bfs_expanded);
+    }}
+    bidirectional! {
+        open: (&[], point(5, 7)),
+        wall: (&[point(-1, 3), point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)], point(3, 6)),
+    }
+
+    #[test]
+    fn bidirectional_edge_cases() {
+        let graph = BasicGraph::new(&[point(1, 0), point(-1, 0), point(0, 1), point(0, -1)]);
+        let origin = point(0, 0);
+        assert_eq!(graph.bidirectional_bfs(&origin, &point(3, 3)).goal(), None);
+        assert_eq!(graph.bidirectional_dijkstras(&origin, &point(3, 3)).goal(), None);
+
+        assert_eq!(graph.bidirectional_bfs(&origin, &origin).goal_cost(), Some(0));
+        assert_eq!(graph.bidirectional_dijkstras(&origin, &origin).goal_path().unwrap().len(), 0);
+    }
+
+    // The open (non-#) cells of a map
+    struct OpenGraph {
+        open: HashMap<Point, char>,
+    }
+
+    impl OpenGraph {
+        fn parse(map: &str) -> OpenGraph {
+            let open = map.lines().enumerate()
+                .flat_map(|(y, l)| l.chars().enumerate().map(move |(x, c)| (point(x as i32, y as i32), c)))
+                .filter(|&(_, c)| c != '#')
+                .collect();
+            OpenGraph { open }
+        }
+
+        fn find(&self, c: char) -> Point {
+            *self.open.iter().find(|&(_, &o)| o == c).expect("Not found").0
+        }
+    }
+
+    impl Graph for OpenGraph {
+        type Node = Point;
+        type Weight = i32;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            Direction4::ALL.iter()
+                .map(|d| source + d.vector())
+                .filter(|p| self.open.contains_key(p))
+                .map(|p| Edge::new(1, *source, p))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn adjacency() {
+        let graph = OpenGraph::parse("#######\n#A...B#\n#.###.#\n#.....#\n###.###\n#######");
+        let (a, b, dead_end) = (graph.find('A'), graph.find('B'), point(3, 4));
+
+        let adjacency = AdjacencyGraph::explore(&graph, &[a]);
+        assert_eq!(adjacency.len(), graph.open.len());
+        assert_eq!(adjacency.edge_count(), 26);
+        assert_eq!(adjacency.bfs_all(&a).costs().collect::<HashMap<_, _>>(),
+                   graph.bfs_all(&a).costs().collect::<HashMap<_, _>>());
+
+        // Only A and B, the junction at (3, 3), and the dead end below it remain
+        let compressed = adjacency.compress(|p| graph.open[p] != '.');
+        assert_eq!(compressed.nodes().cloned().collect::<HashSet<_>>(),
+                   [a, b, point(3, 3), dead_end].iter().cloned().collect());
+        assert_eq!(compressed.edge_count(), 8);
+        // The shorter of the two corridors between A and B is kept
+        let a_to_b: Vec<_> = compressed.neighbors(&a).into_iter().filter(|e| e.dest() == &b).collect();
+        assert_eq!(a_to_b.len(), 1);
+        assert_eq!(a_to_b[0].weight(), 4);
+
+        for (start, goal) in &[(a, b), (a, dead_end), (b, dead_end)] {
+            assert_eq!(compressed.dijkstras(start, |n| n == goal).goal_cost(),
+                       graph.bfs(start, |n| n == goal).goal_cost());
+        }
+    }
+
+    #[test]
+    fn flood_fill() {
+        let graph = OpenGraph::parse("#######\n#A...B#\n#.###.#\n#.....#\n###.###\n#######");
+        let layers = graph.flood_fill(&graph.find('A'));
+        assert_eq!(layers.iter().map(|l| l.len()).collect::<Vec<_>>(), vec!(1, 2, 2, 2, 2, 3, 1));
+        // The two routes around the loop meet at the far corner
+        assert_eq!(layers.last().unwrap(), &vec!(point(5, 3)));
+
+        let costs = graph.bfs_all(&graph.find('A'));
+        for (steps, layer) in layers.iter().enumerate() {
+            for node in layer {
+                assert_eq!(costs.cost(node), Some(steps as i32));
+            }
+        }
+    }
+
+    #[test]
+    fn regions() {
+        let grid = Grid::parse("AAAA\nBBCD\nBBCC\nEEEC", Some).unwrap();
+        let regions = super::regions(&grid);
+        let measures: Vec<_> = regions.iter().map(|r| (grid[*r.cells().next().unwrap()], r.area(), r.perimeter())).collect();
+        assert_eq!(measures, vec!(('A', 4, 10), ('B', 4, 8), ('C', 4, 10), ('D', 1, 4), ('E', 3, 8)));
+        assert!(regions[2].contains(point(3, 3)));
+
+        // Equal values that aren't adjacent are separate regions
+        let grid = Grid::parse("OOO\nOXO\nOOO\nOXO", Some).unwrap();
+        assert_eq!(label_regions(&grid).render(|&l| std::char::from_digit(l as u32, 10).unwrap()),
+                   "000\n010\n000\n020");
+        assert_eq!(super::regions(&grid)[0].perimeter(), 20);
+    }
+
+    #[test]
+    fn compress_cycle() {
+        // With nothing kept, a loop has no junctions or dead ends to anchor it
+        let graph = OpenGraph::parse("#####\n#...#\n#.#.#\n#...#\n#####");
+        let adjacency = AdjacencyGraph::explore(&graph, &[point(1, 1)]);
+        assert_eq!(adjacency.len(), 8);
+        assert!(adjacency.compress(|_| false).is_empty());
+
+        let compressed = adjacency.compress(|p| p == &point(1, 1));
+        assert_eq!(compressed.len(), 1);
+        assert_eq!(compressed.edge_count(), 0);
+    }
+
+    // A graph made of (source, dest, weight) edges
+    struct EdgeList(Vec<(char, char, i32)>);
+
+    impl EdgeList {
+        fn directed(edges: &[(char, char, i32)]) -> EdgeList {
+            EdgeList(edges.to_vec())
+        }
+
+        fn undirected(edges: &[(char, char, i32)]) -> EdgeList {
+            EdgeList(edges.iter().flat_map(|&(s, d, w)| vec!((s, d, w), (d, s, w))).collect())
+        }
+    }
+
+    impl Graph for EdgeList {
+        type Node = char;
+        type Weight = i32;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node, Self::Weight>> {
+            self.0.iter().filter(|e| e.0 == *source).map(|&(s, d, w)| Edge::new(w, s, d)).collect()
+        }
+    }
+
+    fn sorted(mut groups: Vec<Vec<char>>) -> Vec<String> {
+        let mut groups: Vec<String> = groups.iter_mut().map(|g| { g.sort(); g.iter().collect() }).collect();
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn connected_components() {
+        let graph = EdgeList::undirected(&[('a', 'b', 1), ('b', 'c', 1), ('d', 'e', 1)]);
+        let components = graph.connected_components(&['a', 'b', 'c', 'd', 'e', 'f']);
+        assert_eq!(sorted(components), vec!("abc", "de", "f"));
+    }
+
+    #[test]
+    fn topological_sort() {
+        let graph = EdgeList::directed(&[('a', 'b', 1), ('a', 'c', 1), ('b', 'd', 1), ('c', 'd', 1), ('e', 'c', 1)]);
+        let order = graph.topological_sort(&['a', 'e']).unwrap();
+        assert_eq!(order.iter().collect::<HashSet<_>>().len(), 5);
+        let position = |n: &char| order.iter().position(|o| o == n).unwrap();
+        for (source, dest, _) in &graph.0 {
+            assert!(position(source) < position(dest), "{} should precede {}: {:?}", source, dest, order);
+        }
+        assert_eq!(graph.topological_sort(&['c']), Ok(vec!('c', 'd')));
+    }
+
+    #[test]
+    fn topological_sort_cycle() {
+        let graph = EdgeList::directed(&[('d', 'a', 1), ('a', 'b', 1), ('b', 'c', 1), ('c', 'a', 1), ('c', 'e', 1)]);
+        let cycle = graph.topological_sort(&['d']).unwrap_err();
+        assert_eq!(sorted(vec!(cycle.clone())), vec!("abc"));
+        for (i, node) in cycle.iter().enumerate() {
+            let next = cycle[(i + 1) % cycle.len()];
+            assert!(graph.neighbors(node).iter().any(|e| e.dest() == &next), "{:?} is not a cycle", cycle);
+        }
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let graph = EdgeList::directed(&[('a', 'b', 1), ('b', 'c', 1), ('c', 'a', 1), ('c', 'd', 1),
+                                         ('d', 'e', 1), ('e', 'd', 1), ('f', 'e', 1)]);
+        let components = graph.strongly_connected_components(&['a', 'f']);
+        assert_eq!(sorted(components.clone()), vec!("abc", "de", "f"));
+        let component = |n: &char| components.iter().position(|c| c.contains(n)).unwrap();
+        for (source, dest, _) in &graph.0 {
+            assert!(component(dest) <= component(source), "{:?} is not in reverse topological order", components);
+        }
+    }
+
+    #[test]
+    fn minimum_spanning_tree() {
+        let graph = EdgeList::undirected(&[('a', 'b', 4), ('a', 'c', 1), ('b', 'c', 2), ('b', 'd', 5),
+                                           ('c', 'd', 8), ('d', 'e', 3), ('f', 'g', 1)]);
+        let tree = graph.minimum_spanning_tree(&'a');
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.iter().map(|e| e.weight()).sum::<i32>(), 11);
+        let reached: HashSet<_> = tree.iter().flat_map(|e| vec!(*e.source(), *e.dest())).collect();
+        assert_eq!(reached, "abcde".chars().collect());
+    }
+
+    #[test]
+    fn all_pairs_shortest_paths() {
+        let graph = EdgeList::directed(&[('a', 'b', 4), ('a', 'c', 1), ('c', 'b', 2), ('b', 'd', 5),
+                                         ('c', 'd', 8), ('d', 'e', 3), ('e', 'a', 1), ('f', 'a', 7)]);
+        let costs = graph.all_pairs_shortest_paths(&['a', 'f']);
+        assert_eq!(costs[&('a', 'b')], 3);
+        assert_eq!(costs[&('f', 'e')], 18);
+        assert_eq!(costs.get(&('a', 'f')), None);
+        for source in "abcdef".chars() {
+            let expected: HashMap<_, _> = graph.dijkstras_all(&source).costs().map(|(&n, c)| ((source, n), c)).collect();
+            let actual: HashMap<_, _> = costs.iter().filter(|((s, _), _)| *s == source).map(|(&k, &v)| (k, v)).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn depth_first() {
+        let graph = OpenGraph::parse("#######\n#A...B#\n#.###.#\n#.....#\n###.###\n#######");
+        let (a, b, dead_end) = (graph.find('A'), graph.find('B'), point(3, 4));
+
+        for (start, goal) in &[(a, b), (a, dead_end), (b, dead_end)] {
+            let expected = graph.bfs(start, |n| n == goal).goal_cost();
+            let dfs = graph.dfs(start, |n| n == goal, |n| *n);
+            let route = dfs.nodes(goal).unwrap();
+            assert_eq!((&route[0], route.last().unwrap()), (start, goal));
+            assert!(dfs.goal_cost() >= expected);
+
+            let iddfs = graph.iddfs(start, |n| n == goal, 10, |n| *n);
+            assert_eq!(iddfs.goal_cost(), expected);
+            assert_eq!(iddfs.reached(), expected.unwrap() as usize + 1);
+        }
+
+        let too_shallow = graph.iddfs(&a, |n| n == &b, 3, |n| *n);
+        assert_eq!((too_shallow.goal(), too_shallow.stop_reason()), (None, StopReason::MaxDepth));
+        let unreachable = graph.iddfs(&a, |n| n == &point(0, 0), 100, |n| *n);
+        assert_eq!((unreachable.goal(), unreachable.stop_reason()), (None, StopReason::Exhausted));
+    }
+
+    #[test]
+    fn memoized_dfs() {
+        let graph = EdgeList::directed(&[('s', 'a', 1), ('s', 'b', 4), ('a', 'b', 2), ('a', 'c', 6), ('b', 'c', 3),
+                                         ('b', 't', 7), ('c', 't', 1)]);
+        let memoized = graph.memoized_dfs(&'s', |n| n == &'t', |n| *n);
+        assert_eq!(memoized.goal_cost(), graph.dijkstras(&'s', |n| n == &'t').goal_cost());
+        assert_eq!(memoized.nodes(&'t').unwrap().iter().collect::<String>(), "sabct");
+        assert_eq!(memoized.reached(), 5);
+
+        let unreachable = graph.memoized_dfs(&'s', |n| n == &'z', |n| *n);
+        assert_eq!((unreachable.goal(), unreachable.stop_reason()), (None, StopReason::Exhausted));
+    }
+
+    #[test]
+    fn memoized_dfs_canonical() {
+        // Each uppercase node is as far from the goal as its lowercase counterpart, so they can share a key
+        let graph = EdgeList::directed(&[('s', 'a', 1), ('s', 'A', 2), ('a', 'c', 1), ('A', 'C', 1), ('a', 'C', 5),
+                                         ('c', 'd', 1), ('C', 'd', 1), ('d', 't', 1)]);
+        let counter = CountingGraph::new(&graph);
+        let expected = graph.dijkstras(&'s', |n| n == &'t').goal_cost();
+
+        assert_eq!(counter.memoized_dfs(&'s', |n| n == &'t', |n| *n).goal_cost(), expected);
+        assert_eq!(counter.take(), 6);
+        let canonical = counter.memoized_dfs(&'s', |n| n == &'t', |n| n.to_ascii_lowercase());
+        assert_eq!(canonical.goal_cost(), expected);
+        assert_eq!(counter.take(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "acyclic")]
+    fn memoized_dfs_cycle() {
+        let graph = EdgeList::directed(&[('d', 'a', 1), ('a', 'b', 1), ('b', 'c', 1), ('c', 'a', 1), ('c', 'e', 1)]);
+        graph.memoized_dfs(&'d', |n| n == &'e', |n| *n);
+    }
+
+    #[test]
+    fn search_stats() {
+        let graph = BasicGraph::new(&[point(-1, 3), point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)]);
+        let counter = CountingGraph::new(&graph);
+        let (start, goal) = (point(0, 0), point(3, 6));
+
+        let mut stats = SearchStats::default();
+        let bfs = counter.bfs_observed(&start, |n| n == &goal, &mut stats);
+        assert_eq!(stats.expanded, counter.take());
+        assert_eq!(stats.discovered, bfs.reached());
+        assert!(stats.revisited > 0);
+        assert!(stats.max_frontier > 0);
+
+        let mut astar_stats = SearchStats::default();
+        let astar = counter.astar_observed(&start, |n| n == &goal, manhattan(goal), &mut astar_stats);
+        assert_eq!(astar_stats.expanded, counter.take());
+        assert_eq!(astar.goal_cost(), bfs.goal_cost());
+        assert!(astar_stats.expanded < stats.expanded);
+    }
+
+    #[test]
+    fn search_renderer() {
+        let blocked = [point(1, 0), point(1, 1)];
+        let graph = BasicGraph::new(&blocked);
+        let (start, goal) = (point(0, 0), point(2, 0));
+        let renderer = || SearchRenderer::new(crate::console::Renderer::new(),
+                                              |p| if blocked.contains(&p) { '#' } else { ' ' });
+
+        // Nothing is painted unless the frames are going somewhere
+        let mut search = renderer();
+        let route = graph.astar_observed(&start, |n| n == &goal, manhattan(goal), &mut search);
+        assert_eq!(route.goal_cost(), Some(4));
+        assert_eq!(search.frame(), "");
+
+        crate::console::set_output(crate::console::Output::Stdout);
+        let mut search = renderer();
+        graph.astar_observed(&start, |n| n == &goal, manhattan(goal), &mut search);
+        let frame = search.frame();
+        assert_eq!(frame.chars().filter(|&c| c == SearchRenderer::PATH).count(), 5);
+        assert_eq!(frame.chars().filter(|&c| c == '#').count(), 2);
+        assert!(frame.contains(SearchRenderer::FRONTIER));
+        search.finish();
+        assert!(crate::console::take_stdout().contains(SearchRenderer::PATH));
+    }
+
+    #[test]
+    fn limits() {
+        let graph = BasicGraph::new(&[]);
+        let origin = point(0, 0);
+        let within = |r: &SearchResult<Point, i32>, dist| r.costs().filter(|&(p, _)| (*p - Point::ORIGIN).grid_len() <= dist).count();
+
+        let depth = graph.bfs_observed(&origin, |_| false, &mut SearchLimits::default().max_depth(3));
+        assert_eq!(depth.stop_reason(), StopReason::MaxDepth);
+        assert_eq!(within(&depth, 3), 25);
+        assert_eq!(depth.max_cost(), Some(4)); // reached, but not expanded
+
+        let cost = graph.dijkstras_observed(&origin, |_| false, &mut SearchLimits::default().max_cost(3));
+        assert_eq!(cost.stop_reason(), StopReason::MaxCost);
+        assert_eq!(cost.reached(), depth.reached());
+
+        // Limits apply to goals, too
+        let goal = point(2, 2);
+        let beyond = graph.bfs_observed(&origin, |n| n == &goal, &mut SearchLimits::default().max_depth(3));
+        assert_eq!((beyond.goal(), beyond.stop_reason()), (None, StopReason::MaxDepth));
+        let found = graph.bfs_observed(&origin, |n| n == &goal, &mut SearchLimits::default().max_depth(4));
+        assert_eq!((found.goal(), found.stop_reason()), (Some(&goal), StopReason::Goal));
+
+        let mut stats = SearchStats::default();
+        let expansions = graph.astar_observed(&origin, |_| false, |_| 0,
+                                              &mut (SearchLimits::default().max_expansions(100), &mut stats));
+        assert_eq!(expansions.stop_reason(), StopReason::MaxExpansions);
+        assert_eq!(stats.expanded, 100);
+    }
+
+    // Cancels the token after the given number of expansions
+    struct CancelAfter(CancellationToken, usize);
+
+    impl SearchObserver<Point, i32> for CancelAfter {
+        fn expanded(&mut self, _node: &Point, _frontier_len: usize) {
+            self.1 -= 1;
+            if self.1 == 0 { self.0.cancel(); }
+        }
+    }
+
+    #[test]
+    fn cancellation() {
+        let graph = BasicGraph::new(&[]);
+        let token = CancellationToken::default();
+        let mut stats = SearchStats::default();
+        let result = graph.bfs_01_observed(&point(0, 0), |_| false,
+            &mut (SearchLimits::default().cancellation(&token), (CancelAfter(token.clone(), 10), &mut stats)));
+        assert_eq!(result.stop_reason(), StopReason::Cancelled);
+        assert_eq!(stats.expanded, 10);
+        assert!(token.is_cancelled());
+
+        // An already-cancelled token stops the search before it starts
+        let result = graph.dijkstras_observed(&point(0, 0), |_| false, &mut SearchLimits::default().cancellation(&token));
+        assert_eq!((result.stop_reason(), result.reached()), (StopReason::Cancelled, 1));
+    }
+
     #[test]
     fn all_paths() {
         // From 2019 Day 15 pt 2 - forms a small room
@@ -296,21 +2115,24 @@ mod tests {
         let bfs_routes = graph.bfs_all(&start);
         let djk_routes = graph.dijkstras_all(&start);
 
-        let bfs_routes_lens: BTreeMap<_,_> = bfs_routes.iter().map(|(&k, v)| (k, v.len() as i32 - 1)).collect();
-        let djk_routes_lens: BTreeMap<_,_> = djk_routes.iter()
-            .map(|(&k, v)| (k, v.iter().map(|e| e.weight()).sum())).collect();
-        let expected_routes: BTreeMap<_,_> = vec!(
-            (point(1, 1), 3), (point(2, 1), 4), (point(1, 2), 2), (point(3, 2), 2),
-            (point(4, 2), 3), (point(1, 3), 1), (point(2, 3), 0), (point(3, 3), 1)
-        ).iter().cloned().collect();
+        let bfs_routes_lens: BTreeMap<_,_> = bfs_routes.costs().map(|(&k, v)| (k, v)).collect();
+        let djk_routes_lens: BTreeMap<_,_> = djk_routes.costs().map(|(&k, v)| (k, v)).collect();
+        let expected_routes: BTreeMap<_,_> = [(point(1, 1), 3), (point(2, 1), 4), (point(1, 2), 2), (point(3, 2), 2),
+            (point(4, 2), 3), (point(1, 3), 1), (point(2, 3), 0), (point(3, 3), 1)].iter().cloned().collect();
         assert_eq!(bfs_routes_lens, expected_routes);
         assert_eq!(djk_routes_lens, expected_routes);
+        assert_eq!(bfs_routes.max_cost(), Some(4));
+        assert_eq!(bfs_routes.goal(), None);
 
-        let bfs_route = graph.bfs(&start, |n| n == &farthest).unwrap();
-        let bfs_all_route = bfs_routes.get(&farthest).unwrap();
+        let bfs_result = graph.bfs(&start, |n| n == &farthest);
+        assert_eq!(bfs_result.goal(), Some(&farthest));
+        let bfs_route = bfs_result.nodes(&farthest).unwrap();
+        let bfs_all_route = bfs_routes.nodes(&farthest).unwrap();
         assert_eq!(bfs_route.len(), bfs_all_route.len());
         // This is not strictly true, but there's only one route to this point for this graph,
         // so it should be reliable for this test case
-        assert_eq!(&bfs_route, bfs_all_route);
+        assert_eq!(bfs_route, bfs_all_route);
+        assert_eq!(djk_routes.path(&farthest).unwrap().len(), 4);
+        assert!(djk_routes.path(&point(9, 9)).is_none());
     }
 }
\ No newline at end of file
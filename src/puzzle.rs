@@ -0,0 +1,159 @@
+// Each day's module exposes a Puzzle, which splits the day into a parse step and one or more parts
+// so that callers (e.g. the benchmark runner) can run and time each stage independently.
+use std::borrow::Borrow;
+use std::fmt;
+use std::time::{Duration, Instant};
+use crate::input;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Answer {
+    label: String,
+    value: String,
+    // Auxiliary data worth reporting alongside the answer, e.g. how it was found
+    details: Vec<(String, String)>,
+    // Diagnostics that aren't part of the answer, e.g. search statistics; interactive runs report
+    // them on stderr once the part has been timed
+    notes: Vec<String>,
+}
+
+impl Answer {
+    pub fn new(label: impl Into<String>, value: impl fmt::Display) -> Answer {
+        Answer { label: label.into(), value: value.to_string(), details: Vec::new(), notes: Vec::new() }
+    }
+
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl fmt::Display) -> Answer {
+        self.details.push((key.into(), value.to_string()));
+        self
+    }
+
+    pub fn with_note(mut self, note: impl fmt::Display) -> Answer {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn label(&self) -> &str { &self.label }
+    pub fn value(&self) -> &str { &self.value }
+    pub fn details(&self) -> &[(String, String)] { &self.details }
+    pub fn notes(&self) -> &[String] { &self.notes }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Multi-line answers (e.g. rendered images) read better starting on their own line
+        let multiline = self.value.contains('\n');
+        write!(f, "{}{}{}", self.label, if multiline { ":\n" } else { ": " }, self.value)?;
+        if !self.details.is_empty() {
+            let details: Vec<_> = self.details.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+            if multiline {
+                write!(f, "\n{}", details.join("\n"))?;
+            } else {
+                write!(f, " ({})", details.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub type Part<T> = fn(&T) -> Answer;
+
+// Returns true if the puzzle was dealt with interactively, and doesn't need to be solved
+pub type Interaction<T> = fn(&T) -> bool;
+
+pub struct Solution {
+    pub parse: Duration,
+    pub parts: Vec<(Answer, Duration)>,
+}
+
+pub struct Puzzle {
+    day: u32,
+    solve: Box<dyn Fn(&str) -> Solution>,
+    interact: Box<dyn Fn(&str) -> bool>,
+}
+
+impl Puzzle {
+    // The parsed input is passed to each part by reference, and parts may take any type the input
+    // can be borrowed as, e.g. a parse function can return a Vec<T> to parts that expect a &[T].
+    pub fn new<T, U>(day: u32, parse: fn(&str) -> T, parts: Vec<Part<U>>) -> Puzzle
+            where T: Borrow<U> + 'static, U: ?Sized + 'static {
+        let solve = move |input: &str| {
+            let start = Instant::now();
+            let data = parse(input);
+            let parse = start.elapsed();
+            let parts = parts.iter()
+                .map(|part| {
+                    let start = Instant::now();
+                    let answer = part(data.borrow());
+                    (answer, start.elapsed())
+                })
+                .collect();
+            Solution { parse, parts }
+        };
+        Puzzle { day, solve: Box::new(solve), interact: Box::new(|_| false) }
+    }
+
+    // Like new(), with an interactive step (e.g. displaying the input, or exploring it by hand) that
+    // callers can run before solving the puzzle. It isn't part of solve(), so it's never timed,
+    // and it's only run when days are run directly, not while benchmarking or verifying.
+    pub fn interactive<T, U>(day: u32, parse: fn(&str) -> T, parts: Vec<Part<U>>, interact: Interaction<U>) -> Puzzle
+            where T: Borrow<U> + 'static, U: ?Sized + 'static {
+        let mut puzzle = Puzzle::new(day, parse, parts);
+        puzzle.interact = Box::new(move |input: &str| interact(parse(input).borrow()));
+        puzzle
+    }
+
+    pub fn day(&self) -> u32 { self.day }
+
+    pub fn input_name(&self) -> String { format!("day{}.txt", self.day) }
+
+    // Runs the interactive step, if any, returning true if the puzzle was dealt with and shouldn't
+    // be solved
+    pub fn interact(&self, input: &str) -> bool {
+        (self.interact)(input)
+    }
+
+    pub fn solve(&self, input: &str) -> Solution {
+        (self.solve)(input)
+    }
+
+    pub fn run(&self) -> Solution {
+        self.solve(&input::read(&self.input_name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Vec<u32> {
+        input.split(',').map(|n| n.parse().unwrap()).collect()
+    }
+
+    fn sum(data: &[u32]) -> Answer { Answer::new("Sum", data.iter().sum::<u32>()) }
+
+    fn max(data: &[u32]) -> Answer { Answer::new("Max", data.iter().max().unwrap()) }
+
+    #[test]
+    fn solve() {
+        let puzzle = Puzzle::new(0, parse, vec!(sum, max));
+        let solution = puzzle.solve("3,1,2");
+        let answers: Vec<_> = solution.parts.iter().map(|(a, _)| a.clone()).collect();
+        assert_eq!(answers, vec!(Answer::new("Sum", 6), Answer::new("Max", 3)));
+    }
+
+    #[test]
+    fn interact() {
+        assert!(!Puzzle::new(0, parse, vec!(sum)).interact("3,1,2"));
+        assert!(Puzzle::interactive(0, parse, vec!(sum), |data: &[u32]| data.len() == 3).interact("3,1,2"));
+        assert!(!Puzzle::interactive(0, parse, vec!(sum), |data: &[u32]| data.len() == 3).interact("3,1"));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::new("Score", 10).to_string(), "Score: 10");
+        assert_eq!(Answer::new("Image", "#.\n.#").to_string(), "Image:\n#.\n.#");
+        assert_eq!(Answer::new("Signal", 5).with_detail("Sequence", "[1, 0]").with_detail("Tries", 2).to_string(),
+                   "Signal: 5 (Sequence: [1, 0], Tries: 2)");
+        assert_eq!(Answer::new("Image", "#.\n.#").with_detail("Size", "2x2").to_string(), "Image:\n#.\n.#\nSize: 2x2");
+        assert_eq!(Answer::new("Score", 10).with_note("Expanded 3 nodes").to_string(), "Score: 10");
+    }
+}
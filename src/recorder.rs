@@ -0,0 +1,192 @@
+// Captures the frames drawn by a console::Renderer and saves them as an animation, either as an
+// asciicast (https://docs.asciinema.org/manual/asciicast/v2/) which can be replayed in a terminal,
+// or as an animated GIF with one block of color per character cell.
+//
+// Recording is enabled for a run with --capture DIR; each renderer that opts in with a name via
+// Renderer::record() writes DIR/NAME.cast or DIR/NAME.gif once it finishes.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+use crate::console::{Color, Palette};
+use crate::gif;
+use crate::report::json_string;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    Cast,
+    Gif,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Cast => "cast",
+            Format::Gif => "gif",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "cast" => Ok(Format::Cast),
+            "gif" => Ok(Format::Gif),
+            _ => Err(format!("Unknown capture format: {} (expected cast or gif)", s)),
+        }
+    }
+}
+
+lazy_static! {
+    static ref CAPTURE: RwLock<Option<(PathBuf, Format)>> = RwLock::new(None);
+}
+
+pub fn set_capture(dir: impl Into<PathBuf>, format: Format) {
+    *CAPTURE.write().expect("Poisoned") = Some((dir.into(), format));
+}
+
+// Frames without an explicit frame rate are recorded at this rate, since they're otherwise drawn
+// as fast as they're generated
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+// The size in pixels of each character cell in a GIF
+const CELL_WIDTH: usize = 4;
+const CELL_HEIGHT: usize = 6;
+
+pub struct Recorder {
+    path: PathBuf,
+    format: Format,
+    frames: Vec<String>,
+}
+
+impl Recorder {
+    pub fn new(path: impl Into<PathBuf>, format: Format) -> Recorder {
+        Recorder { path: path.into(), format, frames: Vec::new() }
+    }
+
+    // Returns a Recorder if capturing was requested for this run
+    pub fn for_name(name: &str) -> Option<Recorder> {
+        CAPTURE.read().expect("Poisoned").as_ref()
+            .map(|(dir, format)| Recorder::new(dir.join(format!("{}.{}", name, format.extension())), *format))
+    }
+
+    pub fn capture(&mut self, frame: &str) {
+        self.frames.push(frame.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool { self.frames.is_empty() }
+
+    pub fn save(&self, palette: Option<Palette>, frame_interval: Option<Duration>) -> io::Result<&Path> {
+        let interval = frame_interval.unwrap_or(DEFAULT_FRAME_INTERVAL);
+        match self.format {
+            Format::Cast => fs::write(&self.path, asciicast(&self.frames, palette, interval))?,
+            Format::Gif => fs::write(&self.path, animate(&self.frames, palette, interval))?,
+        }
+        Ok(&self.path)
+    }
+}
+
+// The largest width and height, in characters, of any frame
+fn dimensions(frames: &[String]) -> (usize, usize) {
+    let width = frames.iter().flat_map(|f| f.lines()).map(|l| l.chars().count()).max().unwrap_or(0);
+    let height = frames.iter().map(|f| f.lines().count()).max().unwrap_or(0);
+    (width, height)
+}
+
+fn asciicast(frames: &[String], palette: Option<Palette>, interval: Duration) -> String {
+    let (width, height) = dimensions(frames);
+    let mut out = format!("{{\"version\": 2, \"width\": {}, \"height\": {}}}\n", width, height);
+    let mut front = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let data = crate::console::redraw(&front, frame, palette);
+        out.push_str(&format!("[{:.3}, \"o\", {}]\n", (interval * i as u32).as_secs_f64(), json_string(&data)));
+        front = frame.lines().map(String::from).collect();
+    }
+    out
+}
+
+const BACKGROUND: u8 = 0;
+const FOREGROUND: u8 = 1;
+const COLORS: [Color; 6] = [Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan];
+
+fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Red => [205, 49, 49],
+        Color::Green => [13, 188, 121],
+        Color::Yellow => [229, 229, 16],
+        Color::Blue => [36, 114, 200],
+        Color::Magenta => [188, 63, 188],
+        Color::Cyan => [17, 168, 205],
+    }
+}
+
+fn color_index(c: char, palette: Option<Palette>) -> u8 {
+    if c.is_whitespace() { return BACKGROUND; }
+    match palette.and_then(|p| p(c)) {
+        Some(color) => 2 + COLORS.iter().position(|&k| k == color).expect("All colors listed") as u8,
+        None => FOREGROUND,
+    }
+}
+
+fn animate(frames: &[String], palette: Option<Palette>, interval: Duration) -> Vec<u8> {
+    let (width, height) = dimensions(frames);
+    let (pixel_width, pixel_height) = (width * CELL_WIDTH, height * CELL_HEIGHT);
+    let mut colors = vec!([0, 0, 0], [229, 229, 229]);
+    colors.extend(COLORS.iter().map(|&c| rgb(c)));
+    let delay = (interval.as_millis() / 10).clamp(2, u16::MAX as u128) as u16;
+
+    let frames: Vec<_> = frames.iter()
+        .map(|frame| {
+            let mut pixels = vec!(BACKGROUND; pixel_width * pixel_height);
+            for (y, line) in frame.lines().enumerate() {
+                for (x, c) in line.chars().enumerate() {
+                    let color = color_index(c, palette);
+                    for row in y * CELL_HEIGHT..(y + 1) * CELL_HEIGHT {
+                        let start = row * pixel_width + x * CELL_WIDTH;
+                        pixels[start..start + CELL_WIDTH].iter_mut().for_each(|p| *p = color);
+                    }
+                }
+            }
+            gif::Frame { pixels, delay }
+        })
+        .collect();
+    gif::encode(pixel_width as u16, pixel_height as u16, &colors, &frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<String> { vec!("#O\n  ".into(), "#  O".into()) }
+
+    fn palette(c: char) -> Option<Color> { if c == 'O' { Some(Color::Red) } else { None } }
+
+    #[test]
+    fn cast() {
+        assert_eq!(asciicast(&frames(), None, Duration::from_millis(250)), concat!(
+            "{\"version\": 2, \"width\": 4, \"height\": 2}\n",
+            "[0.000, \"o\", \"\\u001b[2K#O\\n\\u001b[2K  \\n\\u001b[J\"]\n",
+            "[0.250, \"o\", \"\\u001b[2F\\u001b[2K#  O\\n\\u001b[J\"]\n"));
+    }
+
+    #[test]
+    fn gif() {
+        let gif = animate(&frames(), Some(palette), Duration::from_millis(250));
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(u16::from_le_bytes([gif[6], gif[7]]) as usize, 4 * CELL_WIDTH);
+        assert_eq!(u16::from_le_bytes([gif[8], gif[9]]) as usize, 2 * CELL_HEIGHT);
+    }
+
+    parameterized_test::create!{ colors, (c, expected), {
+        assert_eq!(color_index(c, Some(palette)), expected);
+    }}
+    colors! {
+        space: (' ', BACKGROUND),
+        wall: ('#', FOREGROUND),
+        ball: ('O', 2),
+    }
+}
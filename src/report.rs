@@ -0,0 +1,147 @@
+// Formats the results of running one or more days, either as human-readable text or as JSON or CSV
+// records (one per part) for consumption by other tools.
+use std::fmt::Write;
+use std::str::FromStr;
+use crate::puzzle::Solution;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format: {} (expected text, json, or csv)", s)),
+        }
+    }
+}
+
+pub fn format(format: Format, results: &[(u32, Solution)]) -> String {
+    match format {
+        Format::Text => text(results),
+        Format::Json => json(results),
+        Format::Csv => csv(results),
+    }
+}
+
+fn text(results: &[(u32, Solution)]) -> String {
+    let mut out = String::new();
+    for (day, solution) in results {
+        if results.len() > 1 { writeln!(out, "Day {}", day).unwrap(); }
+        for (answer, _) in &solution.parts {
+            writeln!(out, "{}", answer).unwrap();
+        }
+    }
+    out
+}
+
+fn json(results: &[(u32, Solution)]) -> String {
+    let mut records = Vec::new();
+    for (day, solution) in results {
+        for (i, (answer, elapsed)) in solution.parts.iter().enumerate() {
+            let details: Vec<_> = answer.details().iter()
+                .map(|(k, v)| format!("{}: {}", json_string(k), json_string(v)))
+                .collect();
+            records.push(format!(
+                "  {{\"day\": {}, \"part\": {}, \"label\": {}, \"answer\": {}, \"elapsed_ns\": {}, \"parse_ns\": {}, \"details\": {{{}}}}}",
+                day, i + 1, json_string(answer.label()), json_string(answer.value()),
+                elapsed.as_nanos(), solution.parse.as_nanos(), details.join(", ")));
+        }
+    }
+    if records.is_empty() { return "[]\n".into(); }
+    format!("[\n{}\n]\n", records.join(",\n"))
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Details are flattened into a single column of semicolon-separated key=value pairs
+fn csv(results: &[(u32, Solution)]) -> String {
+    let mut out = String::from("day,part,label,answer,elapsed_ns,parse_ns,details\n");
+    for (day, solution) in results {
+        for (i, (answer, elapsed)) in solution.parts.iter().enumerate() {
+            let details: Vec<_> = answer.details().iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            writeln!(out, "{},{},{},{},{},{},{}", day, i + 1, csv_field(answer.label()), csv_field(answer.value()),
+                     elapsed.as_nanos(), solution.parse.as_nanos(), csv_field(&details.join(";"))).unwrap();
+        }
+    }
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::puzzle::Answer;
+
+    fn results() -> Vec<(u32, Solution)> {
+        let parts = vec!(
+            (Answer::new("Max Signal", 42).with_detail("Sequence", "[1, 0]"), Duration::from_nanos(20)),
+            (Answer::new("Say \"hi\"", "#.\n.#"), Duration::from_nanos(30)),
+        );
+        vec!((7, Solution { parse: Duration::from_nanos(10), parts }))
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(format(Format::Text, &results()), "Max Signal: 42 (Sequence: [1, 0])\nSay \"hi\":\n#.\n.#\n");
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(format(Format::Json, &results()), concat!(
+            "[\n",
+            "  {\"day\": 7, \"part\": 1, \"label\": \"Max Signal\", \"answer\": \"42\", \"elapsed_ns\": 20, \"parse_ns\": 10, \"details\": {\"Sequence\": \"[1, 0]\"}},\n",
+            "  {\"day\": 7, \"part\": 2, \"label\": \"Say \\\"hi\\\"\", \"answer\": \"#.\\n.#\", \"elapsed_ns\": 30, \"parse_ns\": 10, \"details\": {}}\n",
+            "]\n"));
+        assert_eq!(format(Format::Json, &[]), "[]\n");
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(format(Format::Csv, &results()), concat!(
+            "day,part,label,answer,elapsed_ns,parse_ns,details\n",
+            "7,1,Max Signal,42,20,10,\"Sequence=[1, 0]\"\n",
+            "7,2,\"Say \"\"hi\"\"\",\"#.\n.#\",30,10,\n"));
+    }
+
+    parameterized_test::create!{ formats, (s, expected), {
+        assert_eq!(s.parse::<Format>(), expected);
+    }}
+    formats! {
+        text: ("text", Ok(Format::Text)),
+        json: ("json", Ok(Format::Json)),
+        csv: ("csv", Ok(Format::Csv)),
+        unknown: ("xml", Err("Unknown format: xml (expected text, json, or csv)".to_string())),
+    }
+}
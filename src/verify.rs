@@ -0,0 +1,183 @@
+// Checks each day's answers against the expected answers recorded in an answers.txt manifest next
+// to the inputs, so that changes to shared code (e.g. intcode or pathfinding) which break a
+// solution are caught immediately.
+//
+// $ cargo run --release -- verify [DAY...]
+// $ cargo run --release -- verify --record data/answers.txt   # after replacing the inputs
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use crate::console;
+use crate::input;
+use crate::puzzle::Puzzle;
+
+pub const MANIFEST: &str = "answers.txt";
+
+pub type Answers = BTreeMap<(u32, usize), String>;
+
+// Answers are stored one per line as "DAY PART ANSWER"; newlines and backslashes in the answer are
+// escaped. Trailing whitespace is not significant, see normalize().
+pub fn parse_manifest(manifest: &str) -> Result<Answers, String> {
+    manifest.lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .map(|l| {
+            let parts: Vec<_> = l.splitn(3, ' ').collect();
+            if parts.len() != 3 { return Err(format!("Invalid manifest line: {}", l)); }
+            let day = parts[0].parse().map_err(|e| format!("Invalid day in {}: {}", l, e))?;
+            let part = parts[1].parse().map_err(|e| format!("Invalid part in {}: {}", l, e))?;
+            Ok(((day, part), unescape(parts[2])?))
+        })
+        .collect()
+}
+
+pub fn format_manifest(answers: &Answers) -> String {
+    answers.iter()
+        .map(|((day, part), answer)| format!("{} {} {}\n", day, part, escape(&normalize(answer))))
+        .collect()
+}
+
+fn escape(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(answer: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = answer.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' { out.push(c); continue; }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('\\') => out.push('\\'),
+            other => return Err(format!("Invalid escape \\{} in {}", other.map(String::from).unwrap_or_default(), answer)),
+        }
+    }
+    Ok(out)
+}
+
+// Rendered images may carry trailing spaces, which are easy to lose when editing the manifest
+fn normalize(answer: &str) -> String {
+    answer.lines().map(|l| l.trim_end()).collect::<Vec<_>>().join("\n")
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Mismatch {
+    pub day: u32,
+    pub part: usize,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |answer: &Option<String>| match answer {
+            Some(answer) if answer.contains('\n') => format!("\n{}\n", answer),
+            Some(answer) => answer.clone(),
+            None => "<none>".into(),
+        };
+        write!(f, "Day {} part {}: expected {} but was {}",
+               self.day, self.part, show(&self.expected), show(&self.actual))
+    }
+}
+
+pub fn answers(puzzle: &Puzzle) -> Answers {
+    puzzle.run().parts.iter().enumerate()
+        .map(|(i, (answer, _))| ((puzzle.day(), i + 1), answer.value().to_string()))
+        .collect()
+}
+
+// Reports every part of the given day whose answer differs from the expected answer, including
+// parts that are missing from either the manifest or the solution.
+pub fn compare(day: u32, actual: &Answers, expected: &Answers) -> Vec<Mismatch> {
+    let mut keys: Vec<_> = actual.keys().chain(expected.keys()).filter(|(d, _)| *d == day).cloned().collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .map(|key| (key, expected.get(&key).map(|a| normalize(a)), actual.get(&key).map(|a| normalize(a))))
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|((day, part), expected, actual)| Mismatch { day, part, expected, actual })
+        .collect()
+}
+
+pub fn verify(puzzle: &Puzzle, expected: &Answers) -> Vec<Mismatch> {
+    compare(puzzle.day(), &answers(puzzle), expected)
+}
+
+pub fn main(puzzles: &[Puzzle], args: &[String]) -> Result<(), String> {
+    // Only the verification results are of interest
+    console::set_output(console::Output::Off);
+    let mut record = None;
+    let mut days = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => record = Some(args.next().ok_or("--record requires a value")?.clone()),
+            day => days.push(day.parse::<u32>().map_err(|_| format!("Unexpected argument: {}", day))?),
+        }
+    }
+    let puzzles = puzzles.iter().filter(|p| days.is_empty() || days.contains(&p.day()));
+
+    if let Some(record) = record {
+        let answers: Answers = puzzles.flat_map(answers).collect();
+        return fs::write(&record, format_manifest(&answers)).map_err(|e| format!("Cannot write {}: {}", record, e));
+    }
+
+    let expected = parse_manifest(&input::try_read(MANIFEST).map_err(|e| e.to_string())?)?;
+    let mut mismatches = Vec::new();
+    for puzzle in puzzles {
+        let found = verify(puzzle, &expected);
+        println!("Day {}: {}", puzzle.day(), if found.is_empty() { "OK" } else { "FAILED" });
+        mismatches.extend(found);
+    }
+    if !mismatches.is_empty() {
+        let mismatches: Vec<_> = mismatches.iter().map(|m| m.to_string()).collect();
+        return Err(format!("Mismatched answers:\n{}", mismatches.join("\n")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected() -> Answers {
+        parse_manifest(&input::read(MANIFEST)).expect("Valid manifest")
+    }
+
+    fn check(puzzle: &Puzzle) {
+        let mismatches = verify(puzzle, &expected());
+        assert!(mismatches.is_empty(), "{}", mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>().join("\n"));
+    }
+
+    // Solves every day, like the verify subcommand, which is too slow for the default test run; use
+    // `cargo test --release -- --ignored` to include it
+    #[test]
+    #[ignore]
+    fn all_days() {
+        for puzzle in crate::puzzles().iter() {
+            check(puzzle);
+        }
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let answers: Answers = [((1, 1), "123".to_string()), ((8, 2), "# \n #\\".to_string())].iter().cloned().collect();
+        let manifest = format_manifest(&answers);
+        assert_eq!(manifest, "1 1 123\n8 2 #\\n #\\\\\n");
+        let parsed = parse_manifest(&manifest).unwrap();
+        assert_eq!(parsed[&(8, 2)], "#\n #\\");
+
+        assert!(parse_manifest("1 1").is_err());
+        assert!(parse_manifest("1 1 bad\\escape").is_err());
+    }
+
+    #[test]
+    fn mismatches() {
+        let actual = parse_manifest("99 1 abc\n99 2 # \\n #\n99 4 def").unwrap();
+        let expected = parse_manifest("99 1 abc\n99 2 #\\n #  \n99 3 xyz\n99 4 ghi\n1 1 ignored").unwrap();
+        assert_eq!(compare(99, &actual, &expected), vec!(
+            Mismatch { day: 99, part: 3, expected: Some("xyz".into()), actual: None },
+            Mismatch { day: 99, part: 4, expected: Some("ghi".into()), actual: Some("def".into()) },
+        ));
+    }
+}